@@ -3,6 +3,9 @@ use thiserror::Error;
 
 pub type AzCliResult<T> = Result<T, AzCliError>;
 
+/// Exit status the Azure CLI uses when the requested resource does not exist.
+const NOT_FOUND_EXIT_CODE: i32 = 3;
+
 #[derive(Debug, Error)]
 pub enum AzCliError {
     #[error("Azure CLI (az) executable not found. Install Azure CLI to continue.")]
@@ -16,3 +19,16 @@ pub enum AzCliError {
     #[error("Failed to execute Azure CLI: {0}")]
     Io(#[from] io::Error),
 }
+
+impl AzCliError {
+    /// Whether az reported that the requested resource does not exist.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            AzCliError::CommandFailure {
+                code: Some(NOT_FOUND_EXIT_CODE),
+                ..
+            }
+        )
+    }
+}
@@ -3,7 +3,8 @@ use crate::{
     cache::{CacheStore, CachedAppConfig, CachedKeyVault, SetupCache},
     context::{
         ActiveContext, AppSelection, Context, ContextStore, DEFAULT_APP_CONFIG_ENDPOINT,
-        SubscriptionMetadata, default_appconfig_endpoint, default_separator,
+        SecretNaming, SubscriptionMetadata, default_appconfig_endpoint, default_separator,
+        is_protected_label,
     },
 };
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
@@ -87,13 +88,26 @@ pub fn setup() {
     };
 
     let mut preserved_app = AppSelection::default();
+    let mut secret_naming = SecretNaming::default();
+    let mut protected_labels = Vec::new();
 
-    if let Some(existing) = context.active.take() {
-        if existing.subscription.id == selected.subscription_id
-            && existing.config_name == selected.config_name
-        {
+    // Secret naming and protected labels belong to the store; a different store takes them
+    // from one of its saved contexts, or starts from the defaults.
+    if let Some(existing) = context.active.take()
+        && existing.config_name == selected.config_name
+    {
+        secret_naming = existing.secret_naming;
+        protected_labels = existing.protected_labels;
+        if existing.subscription.id == selected.subscription_id {
             preserved_app = existing.app;
         }
+    } else if let Some(saved) = context
+        .saved
+        .values()
+        .find(|saved| saved.config_name == selected.config_name)
+    {
+        secret_naming = saved.secret_naming.clone();
+        protected_labels = saved.protected_labels.clone();
     }
 
     let active = ActiveContext {
@@ -105,6 +119,8 @@ pub fn setup() {
         endpoint: selected.endpoint.clone(),
         separator,
         app: preserved_app,
+        secret_naming,
+        protected_labels,
     };
 
     context.active = Some(active);
@@ -128,6 +144,182 @@ pub fn sync() {
     }
 }
 
+/// Saves the active context under `name` so other commands can target it.
+pub fn save_context_as(name: &str) {
+    let name = name.trim();
+    if name.is_empty() {
+        eprintln!("Context name cannot be empty.");
+        return;
+    }
+
+    let Some((store, mut context)) = load_context() else {
+        return;
+    };
+    let Some(active) = context.active.clone() else {
+        missing_setup_message();
+        return;
+    };
+
+    let replaced = context.saved.insert(name.to_string(), active).is_some();
+    if save_context(&store, &context) {
+        if replaced {
+            println!("Updated saved context '{name}'.");
+        } else {
+            println!("Saved the active context as '{name}'.");
+        }
+    }
+}
+
+pub fn list_contexts() {
+    let Some((_, context)) = load_context() else {
+        return;
+    };
+
+    if context.saved.is_empty() {
+        println!("No saved contexts. Run `azac context save <name>` to add one.");
+        return;
+    }
+
+    for (name, saved) in &context.saved {
+        let active = context.active.as_ref().is_some_and(|active| {
+            active.config_name == saved.config_name
+                && active.app.name == saved.app.name
+                && active.app.label == saved.app.label
+        });
+        let marker = if active { "*" } else { " " };
+        println!(
+            "{} {:<16} {}",
+            marker,
+            name,
+            describe_context(saved).dimmed()
+        );
+    }
+}
+
+/// Makes the saved context `name` the active one.
+pub fn use_context(name: &str) {
+    let Some((store, mut context)) = load_context() else {
+        return;
+    };
+    let Some(saved) = context.saved.get(name).cloned() else {
+        eprintln!("No saved context named '{name}'.");
+        return;
+    };
+
+    let mut saved = saved;
+    // Keep labels protected on the same store after this snapshot was saved.
+    if let Some(active) = context
+        .active
+        .as_ref()
+        .filter(|active| active.config_name == saved.config_name)
+    {
+        for label in &active.protected_labels {
+            if !is_protected_label(&saved.protected_labels, Some(label)) {
+                saved.protected_labels.push(label.clone());
+            }
+        }
+    }
+
+    let description = describe_context(&saved);
+    context.active = Some(saved);
+    if save_context(&store, &context) {
+        println!("Switched to '{name}' ({description}).");
+    }
+}
+
+pub fn remove_context(name: &str) {
+    let Some((store, mut context)) = load_context() else {
+        return;
+    };
+    if context.saved.remove(name).is_none() {
+        eprintln!("No saved context named '{name}'.");
+        return;
+    }
+
+    if save_context(&store, &context) {
+        println!("Removed saved context '{name}'.");
+    }
+}
+
+/// Marks `label` as protected (or unprotects it) in the active store, including every saved
+/// context for that store.
+pub fn protect_label(label: &str, protect: bool) {
+    let label = label.trim();
+    if label.is_empty() {
+        eprintln!("Label cannot be empty.");
+        return;
+    }
+
+    let Some((store, mut context)) = load_context() else {
+        return;
+    };
+    let Some(active) = context.active.as_mut() else {
+        missing_setup_message();
+        return;
+    };
+
+    let protected = is_protected_label(&active.protected_labels, Some(label));
+    if protect == protected {
+        if protect {
+            println!("Label '{label}' is already protected.");
+        } else {
+            println!("Label '{label}' is not protected.");
+        }
+        return;
+    }
+
+    if protect {
+        active.protected_labels.push(label.to_string());
+    } else {
+        active
+            .protected_labels
+            .retain(|protected| !protected.eq_ignore_ascii_case(label));
+    }
+
+    let config_name = active.config_name.clone();
+    let protected_labels = active.protected_labels.clone();
+    for saved in context
+        .saved
+        .values_mut()
+        .filter(|saved| saved.config_name == config_name)
+    {
+        saved.protected_labels = protected_labels.clone();
+    }
+
+    if save_context(&store, &context) {
+        if protect {
+            println!("Destructive commands on label '{label}' now require typing its name.");
+        } else {
+            println!("Label '{label}' is no longer protected.");
+        }
+    }
+}
+
+fn describe_context(context: &ActiveContext) -> String {
+    let protected = if context.protected_labels.is_empty() {
+        String::new()
+    } else {
+        format!(" (protected: {})", context.protected_labels.join(", "))
+    };
+    format!(
+        "config {}, app {}, label {}{}",
+        context.config_name,
+        context
+            .app
+            .name
+            .as_deref()
+            .filter(|name| !name.is_empty())
+            .unwrap_or("none"),
+        context
+            .app
+            .label
+            .as_deref()
+            .filter(|label| !label.is_empty())
+            .unwrap_or("none"),
+        protected
+    )
+}
+
 struct ConfigOption {
     subscription_id: String,
     subscription_name: String,
@@ -166,12 +358,12 @@ pub mod app {
     };
     use owo_colors::OwoColorize;
     use serde::Deserialize;
-    use serde_json;
 
     use super::MenuItem;
     use crate::{
         azcli::{error::AzCliResult, run::az, subscription},
         cache::CachedKeyVault,
+        keyvault::KeyVaultReference,
     };
 
     pub fn select_app() {
@@ -233,11 +425,11 @@ pub mod app {
         for entry in entries {
             if let Some(idx) = entry.key.rfind(&separator) {
                 let prefix = entry.key[..idx].to_string();
-                let stats = apps.entry(prefix).or_insert_with(AppStats::default);
-                if let Some(label) = entry.label.as_deref().map(str::trim) {
-                    if !label.is_empty() {
-                        stats.labels.insert(label.to_string());
-                    }
+                let stats = apps.entry(prefix).or_default();
+                if let Some(label) = entry.label.as_deref().map(str::trim)
+                    && !label.is_empty()
+                {
+                    stats.labels.insert(label.to_string());
                 }
                 if let Some(vault) = keyvault_name(&entry) {
                     stats.keyvaults.insert(vault);
@@ -246,9 +438,7 @@ pub mod app {
         }
 
         if let Some(current) = current_app.as_ref() {
-            let stats = apps
-                .entry(current.clone())
-                .or_insert_with(AppStats::default);
+            let stats = apps.entry(current.clone()).or_default();
             if let Some(label) = current_label
                 .as_deref()
                 .map(str::trim)
@@ -325,9 +515,7 @@ pub mod app {
                 .map(|kv| kv.subscription_id.clone());
         }
 
-        if !super::save_context(&store, &context) {
-            return;
-        }
+        super::save_context(&store, &context);
     }
 
     fn select_keyvault(
@@ -486,8 +674,6 @@ pub mod app {
     struct KeyValue {
         key: String,
         label: Option<String>,
-        #[serde(rename = "contentType")]
-        content_type: Option<String>,
         value: Option<String>,
     }
 
@@ -519,130 +705,19 @@ pub mod app {
     }
 
     fn keyvault_name(entry: &KeyValue) -> Option<String> {
-        if let Some(value) = entry.value.as_deref() {
-            if let Some(uri) =
-                parse_keyvault_reference(value).or_else(|| parse_keyvault_json(value))
-            {
-                return vault_name_from_uri(&uri);
-            }
-        }
-
-        if entry
-            .content_type
-            .as_deref()
-            .map(|ct| ct.contains("keyvaultref"))
-            .unwrap_or(false)
-        {
-            if let Some(value) = entry.value.as_deref() {
-                if let Some(uri) = parse_keyvault_json(value) {
-                    return vault_name_from_uri(&uri);
-                }
-            }
-        }
-
-        None
-    }
-
-    fn parse_keyvault_reference(value: &str) -> Option<String> {
-        const PREFIX: &str = "@Microsoft.KeyVault(";
-        const SUFFIX: &str = ")";
-
-        if !value.starts_with(PREFIX) || !value.ends_with(SUFFIX) {
-            return None;
-        }
-
-        let inner = &value[PREFIX.len()..value.len() - SUFFIX.len()];
-        let trimmed = inner.trim();
-        if trimmed.is_empty() {
-            return None;
-        }
-
-        let mut secret_uri = None;
-        let mut vault_name = None;
-        let mut secret_name = None;
-        let mut secret_version = None;
-
-        for part in trimmed.split(';') {
-            let part = part.trim();
-            if part.is_empty() {
-                continue;
-            }
-
-            let (key, value) = match part.split_once('=') {
-                Some((k, v)) => (k.trim().to_ascii_lowercase(), v.trim()),
-                None => continue,
-            };
-
-            if value.is_empty() {
-                continue;
-            }
-
-            match key.as_str() {
-                "secreturi" => secret_uri = Some(value.to_string()),
-                "vaultname" => vault_name = Some(value.to_string()),
-                "secretname" => secret_name = Some(value.to_string()),
-                "secretversion" => secret_version = Some(value.to_string()),
-                _ => {}
-            }
-        }
-
-        if let Some(uri) = secret_uri {
-            return Some(uri);
-        }
-
-        match (vault_name, secret_name) {
-            (Some(vault), Some(secret)) => {
-                let base = if vault.starts_with("http://") || vault.starts_with("https://") {
-                    vault.trim_end_matches('/').to_string()
-                } else {
-                    format!("https://{}.vault.azure.net", vault.trim_end_matches('/'))
-                };
-
-                let mut uri = format!("{}/secrets/{}", base, secret.trim_matches('/'));
-                if let Some(version) = secret_version.filter(|v| !v.trim().is_empty()) {
-                    uri.push('/');
-                    uri.push_str(version.trim_matches('/'));
-                }
-
-                Some(uri)
-            }
-            _ => None,
-        }
-    }
-
-    fn parse_keyvault_json(value: &str) -> Option<String> {
-        let json: serde_json::Value = serde_json::from_str(value).ok()?;
-        json.get("uri")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
-            .or_else(|| {
-                json.get("secretUri")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-            })
-    }
-
-    fn vault_name_from_uri(uri: &str) -> Option<String> {
-        let without_scheme = uri.splitn(2, "://").nth(1)?;
-        let host = without_scheme.split('/').next()?.trim();
-        if host.is_empty() {
-            return None;
-        }
-
-        let name = host.split('.').next().unwrap_or(host);
-        if name.is_empty() {
-            None
-        } else {
-            Some(name.to_string())
-        }
+        let value = entry.value.as_deref()?;
+        KeyVaultReference::from_app_service(value)
+            .or_else(|| KeyVaultReference::from_json(value))
+            .map(|reference| reference.vault().to_string())
     }
 }
 
 pub mod kv {
     use std::{
-        collections::{BTreeMap, VecDeque},
+        collections::{BTreeMap, BTreeSet, VecDeque},
         fs,
         path::Path,
+        process::{self, Command},
         sync::{
             Arc, Mutex,
             atomic::{AtomicUsize, Ordering},
@@ -651,13 +726,16 @@ pub mod kv {
         time::Duration,
     };
 
-    use heck::ToUpperCamelCase;
+    use chrono::{DateTime, SecondsFormat, Utc};
+    use clap::ValueEnum;
+    use heck::ToKebabCase;
     use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-    use inquire::{InquireError, Select};
+    use inquire::{Confirm, InquireError, Select, Text};
     use owo_colors::OwoColorize;
     use serde::Deserialize;
     use tabled::{
         Table, Tabled,
+        builder::Builder,
         settings::{
             Color,
             object::Rows,
@@ -666,25 +744,55 @@ pub mod kv {
     };
 
     use super::MenuItem;
-    use crate::azcli::{
-        error::{AzCliError, AzCliResult},
-        run::az,
+    use crate::{
+        azcli::{
+            error::{AzCliError, AzCliResult},
+            run::az,
+        },
+        context::{
+            ActiveContext, SecretCasing, SecretNaming, is_protected_label, validate_secret_name,
+        },
+        convert::{self, EnvNaming, K8sKind, Shell, SourceOptions},
+        entries::{self, EntryValueType, ImportEntry, SecretExport},
+        keyvault::{self, KeyVaultReference, ReferenceKind, display_secret_reference},
+        rotate::{self, RotationPolicy},
     };
 
+    /// Tag written on secrets azac creates so `keyvault gc` can tell them apart.
+    const AZAC_TAG_NAME: &str = "managed-by";
+    const AZAC_TAG_VALUE: &str = "azac";
+    /// Tag marking a version kept readable by `rotate --grace`; holds the time it is disabled.
+    const AZAC_RETIRE_TAG: &str = "azac-retire-after";
+
     #[derive(Debug, Deserialize)]
-    struct KeyValue {
-        key: String,
-        value: Option<String>,
+    pub struct KeyValue {
+        pub key: String,
+        #[serde(default)]
+        pub label: Option<String>,
+        pub value: Option<String>,
         #[serde(rename = "contentType")]
-        content_type: Option<String>,
+        pub content_type: Option<String>,
+        #[serde(default)]
+        pub tags: Option<BTreeMap<String, String>>,
     }
 
     #[derive(Debug, Deserialize)]
     struct SecretValue {
         value: String,
+        #[serde(flatten)]
+        details: ObjectDetails,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct SecretVersion {
+        id: String,
+        #[serde(default)]
+        attributes: SecretAttributes,
+        #[serde(default)]
+        tags: Option<BTreeMap<String, String>>,
     }
 
-    #[derive(Debug)]
+    #[derive(Clone, Debug)]
     struct ActiveKvContext {
         config_name: String,
         endpoint: String,
@@ -692,13 +800,14 @@ pub mod kv {
         app_name: Option<String>,
         label: Option<String>,
         keyvault: Option<String>,
+        secret_naming: SecretNaming,
+        protected_labels: Vec<String>,
     }
 
-    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-    enum EntryValueType {
-        Plain,
-        KeyVault,
-        Prompt,
+    impl ActiveKvContext {
+        fn is_protected(&self) -> bool {
+            is_protected_label(&self.protected_labels, self.label.as_deref())
+        }
     }
 
     #[derive(Tabled)]
@@ -788,29 +897,26 @@ pub mod kv {
             .into_iter()
             .map(|entry| {
                 let key = strip_prefix(&ctx, &entry.key);
-                let (value, from_keyvault) = resolve_value(&entry, false, false);
-
-                let display_value = if from_keyvault {
-                    keyvault_uri_from_entry(&entry)
-                        .and_then(|uri| {
-                            parse_secret_uri(&uri)
-                                .map(|(vault, secret)| format!("{vault}/{secret}"))
-                                .or(Some(truncate_value(&uri, 40)))
-                        })
-                        .unwrap_or_else(|| truncate_value(&value, 40))
-                } else if value.is_empty() {
-                    "(empty)".to_string()
-                } else {
-                    truncate_value(&value, 40)
+                let (value, _) = resolve_value(&entry, false, false);
+
+                let (value_type, display_value) = match keyvault_reference_from_entry(&entry) {
+                    Some(reference) => {
+                        let value_type = match reference.kind {
+                            ReferenceKind::Secret => "keyvault".to_string(),
+                            kind => format!("keyvault {}", kind.label()),
+                        };
+                        (
+                            value_type,
+                            format!("{}/{}", reference.vault(), reference.name),
+                        )
+                    }
+                    None if value.is_empty() => ("plain".to_string(), "(empty)".to_string()),
+                    None => ("plain".to_string(), truncate_value(&value, 40)),
                 };
 
                 KeyListingRow {
                     key,
-                    value_type: if from_keyvault {
-                        "keyvault".to_string()
-                    } else {
-                        "plain".to_string()
-                    },
+                    value_type,
                     value: display_value,
                 }
             })
@@ -829,18 +935,22 @@ pub mod kv {
     }
 
     pub fn show_key(key: &str) {
-        let spinner = create_spinner("Resolving configuration context...");
-        let ctx = match resolve_active_context(true, false) {
-            Some(ctx) => ctx,
-            None => {
-                spinner.finish_and_clear();
-                return;
-            }
+        let Some(ctx) = resolve_active_context(true, false) else {
+            return;
         };
 
-        spinner.set_message(format!("Fetching '{}'...", key));
-        let full_key = prefix_key(&ctx, key);
-        let entry = match show_entry(&ctx, &full_key) {
+        let Some(keys) = expand_keys(&ctx, &[key.to_string()]) else {
+            return;
+        };
+        for key in &keys {
+            show_one_key(&ctx, key);
+        }
+    }
+
+    fn show_one_key(ctx: &ActiveKvContext, key: &str) {
+        let spinner = create_spinner(&format!("Fetching '{}'...", key));
+        let full_key = prefix_key(ctx, key);
+        let entry = match show_entry(ctx, &full_key) {
             Ok(entry) => entry,
             Err(err) => {
                 spinner.finish_and_clear();
@@ -850,9 +960,45 @@ pub mod kv {
         };
         spinner.finish_with_message(format!("Fetched '{}'.", key));
 
-        let display_key = strip_prefix(&ctx, &entry.key);
-        let (value, from_keyvault) = resolve_value(&entry, true, true);
+        let display_key = strip_prefix(ctx, &entry.key);
         let keyvault_uri = keyvault_uri_from_entry(&entry);
+        let (value, from_keyvault, details) = match keyvault_uri.as_deref() {
+            Some(uri) if reference_kind(uri) != ReferenceKind::Secret => {
+                // Certificates and keys have no value to show, only their metadata.
+                let kind = reference_kind(uri).label();
+                let spinner = create_spinner(&format!("Fetching Key Vault {kind}..."));
+                let details = match fetch_object_details(uri) {
+                    Ok(details) => {
+                        spinner.finish_with_message(format!("Key Vault {kind} fetched."));
+                        Some(details)
+                    }
+                    Err(err) => {
+                        spinner.finish_and_clear();
+                        eprintln!("Failed to read Key Vault {kind} {}: {}", uri, err);
+                        None
+                    }
+                };
+                (format!("({kind} reference, value not read)"), true, details)
+            }
+            Some(uri) => {
+                let spinner = create_spinner("Fetching Key Vault secret...");
+                match fetch_secret(uri) {
+                    Ok(secret) => {
+                        spinner.finish_with_message("Key Vault secret fetched.");
+                        (secret.value, true, Some(secret.details))
+                    }
+                    Err(err) => {
+                        spinner.finish_and_clear();
+                        eprintln!("Failed to resolve Key Vault secret {}: {}", uri, err);
+                        (uri.to_string(), true, None)
+                    }
+                }
+            }
+            None => {
+                let (value, from_keyvault) = resolve_value(&entry, false, false);
+                (value, from_keyvault, None)
+            }
+        };
 
         let detail = if value.is_empty() {
             "(empty)".to_string()
@@ -862,17 +1008,68 @@ pub mod kv {
 
         let line = format_key_line(&display_key, &detail, from_keyvault, detail == "(empty)");
         println!("{line}");
-        if from_keyvault {
-            if let Some(secret_uri) = keyvault_uri {
-                println!(
-                    "{}",
-                    format!("  ↳ {}", truncate_value(&secret_uri, 120)).dimmed()
-                );
-            }
+        if from_keyvault && let Some(secret_uri) = keyvault_uri {
+            println!(
+                "{}",
+                format!("  ↳ {}", truncate_value(&secret_uri, 120)).dimmed()
+            );
+        }
+        if let Some(details) = details {
+            print_object_metadata(&details);
+        }
+    }
+
+    fn print_object_metadata(details: &ObjectDetails) {
+        let attributes = &details.attributes;
+        let mut lines = Vec::new();
+
+        let enabled = match attributes.enabled {
+            Some(false) => "no",
+            _ => "yes",
+        };
+        lines.push(("enabled", enabled.to_string()));
+        if let Some(expires) = attributes.expires.as_deref() {
+            let remaining = match days_until(expires) {
+                Some(days) if days < 0 => " (expired)".to_string(),
+                Some(days) => format!(" (in {} days)", days),
+                None => String::new(),
+            };
+            lines.push(("expires", format!("{expires}{remaining}")));
+        }
+        if let Some(not_before) = attributes.not_before.as_deref() {
+            lines.push(("not before", not_before.to_string()));
+        }
+        if let Some(updated) = attributes.updated.as_deref() {
+            lines.push(("updated", updated.to_string()));
+        }
+        if let Some(content_type) = details.content_type.as_deref() {
+            lines.push(("content type", content_type.to_string()));
+        }
+        if let Some(tags) = details.tags.as_ref().filter(|tags| !tags.is_empty()) {
+            let tags: Vec<String> = tags
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect();
+            lines.push(("tags", tags.join(", ")));
+        }
+
+        for (name, value) in lines {
+            println!("{}", format!("    {:<13}{}", name, value).dimmed());
+        }
+    }
+
+    /// Whole days from now until the RFC 3339 timestamp `at`; negative once it has passed.
+    fn days_until(at: &str) -> Option<i64> {
+        let at = DateTime::parse_from_rfc3339(at).ok()?;
+        let remaining = at.with_timezone(&Utc) - Utc::now();
+        if remaining.num_seconds() < 0 {
+            Some(remaining.num_days().min(-1))
+        } else {
+            Some(remaining.num_days())
         }
     }
 
-    pub fn set_key(key: &str, value: &str, use_keyvault: bool) {
+    pub fn set_key(key: &str, value: &str, use_keyvault: bool, shared: SharedSecretPolicy) {
         let Some(ctx) = resolve_active_context(true, false) else {
             return;
         };
@@ -883,13 +1080,34 @@ pub mod kv {
         if let Some(entry) = existing_entry.as_ref() {
             // If the stored value is a Key Vault reference, update the secret directly.
             if let Some(secret_uri) = keyvault_uri_from_entry(entry) {
-                match set_secret_value(&secret_uri, value) {
+                let Some(usage) = load_secret_usage(&ctx) else {
+                    return;
+                };
+                let Some(target) =
+                    resolve_secret_target(&ctx, &usage, &full_key, &secret_uri, shared)
+                else {
+                    return;
+                };
+
+                let result = if target == secret_uri {
+                    roll_secret(&ctx, &full_key, &secret_uri, value).map(|_| ())
+                } else {
+                    set_secret_value(&target, value)
+                        .and_then(|_| write_keyvault_entry(&ctx, &full_key, &target).map(|_| ()))
+                };
+                match result {
                     Ok(_) => {
                         let label_display = ctx.label.as_deref().unwrap_or("(none)");
                         println!(
                             "Updated Key Vault secret for key '{}' in App Configuration '{}' (label: {}).",
                             key, ctx.config_name, label_display
                         );
+                        if target != secret_uri {
+                            println!(
+                                "  ↳ now references label-scoped secret '{}'",
+                                display_secret_reference(&target)
+                            );
+                        }
                     }
                     Err(err) => eprintln!("Failed to update Key Vault secret for '{}': {err}", key),
                 }
@@ -898,7 +1116,10 @@ pub mod kv {
         }
 
         let write_result = if use_keyvault {
-            match build_keyvault_reference(&ctx, &full_key, value) {
+            if !ensure_unique_secret(&ctx, &full_key) {
+                return;
+            }
+            match build_keyvault_reference(&ctx, &full_key, value, shared) {
                 Some(secret_uri) => write_keyvault_entry(&ctx, &full_key, &secret_uri),
                 None => return,
             }
@@ -918,13 +1139,25 @@ pub mod kv {
         }
     }
 
-    pub fn promote_key(key: &str) {
+    pub fn promote_key(key: &str, shared: SharedSecretPolicy) {
         let Some(ctx) = resolve_active_context(true, false) else {
             return;
         };
 
-        let full_key = prefix_key(&ctx, key);
-        let entry = match show_entry(&ctx, &full_key) {
+        let Some(keys) = expand_keys(&ctx, &[key.to_string()]) else {
+            return;
+        };
+        if is_key_pattern(key) {
+            print_key_list(&format!("Matched {}:", count_keys(keys.len())), &keys);
+        }
+        for key in &keys {
+            promote_one_key(&ctx, key, shared);
+        }
+    }
+
+    fn promote_one_key(ctx: &ActiveKvContext, key: &str, shared: SharedSecretPolicy) {
+        let full_key = prefix_key(ctx, key);
+        let entry = match show_entry(ctx, &full_key) {
             Ok(entry) => entry,
             Err(err) => {
                 eprintln!("Failed to fetch key '{}': {err}", key);
@@ -942,12 +1175,16 @@ pub mod kv {
             return;
         };
 
-        let secret_uri = match build_keyvault_reference(&ctx, &full_key, value) {
+        if !ensure_unique_secret(ctx, &full_key) {
+            return;
+        }
+
+        let secret_uri = match build_keyvault_reference(ctx, &full_key, value, shared) {
             Some(uri) => uri,
             None => return,
         };
 
-        match write_keyvault_entry(&ctx, &full_key, &secret_uri) {
+        match write_keyvault_entry(ctx, &full_key, &secret_uri) {
             Ok(_) => {
                 let label_display = ctx.label.as_deref().unwrap_or("(none)");
                 println!(
@@ -959,13 +1196,30 @@ pub mod kv {
         }
     }
 
-    pub fn demote_key(key: &str) {
+    pub fn demote_key(key: &str, yes: bool) {
         let Some(ctx) = resolve_active_context(true, false) else {
             return;
         };
 
-        let full_key = prefix_key(&ctx, key);
-        let entry = match show_entry(&ctx, &full_key) {
+        let Some(keys) = expand_keys(&ctx, &[key.to_string()]) else {
+            return;
+        };
+        print_key_list(
+            &format!("Keys to demote ({}):", describe_kv_context(&ctx)),
+            &keys,
+        );
+        if !confirm_destructive(&ctx, &format!("Demote {}?", count_keys(keys.len())), yes) {
+            println!("No keys demoted.");
+            return;
+        }
+        for key in &keys {
+            demote_one_key(&ctx, key);
+        }
+    }
+
+    fn demote_one_key(ctx: &ActiveKvContext, key: &str) {
+        let full_key = prefix_key(ctx, key);
+        let entry = match show_entry(ctx, &full_key) {
             Ok(entry) => entry,
             Err(err) => {
                 eprintln!("Failed to fetch key '{}': {err}", key);
@@ -978,6 +1232,16 @@ pub mod kv {
             return;
         };
 
+        let kind = reference_kind(&secret_uri);
+        if kind != ReferenceKind::Secret {
+            eprintln!(
+                "Key '{}' references a Key Vault {}, which has no value to demote.",
+                key,
+                kind.label()
+            );
+            return;
+        }
+
         let secret_value = match fetch_secret_value(&secret_uri) {
             Ok(value) => value,
             Err(err) => {
@@ -987,7 +1251,7 @@ pub mod kv {
         };
 
         // Clear content type so we drop the Key Vault reference type.
-        match write_entry(&ctx, &full_key, &secret_value, Some("")) {
+        match write_entry(ctx, &full_key, &secret_value, Some("")) {
             Ok(_) => {
                 let label_display = ctx.label.as_deref().unwrap_or("(none)");
                 println!(
@@ -999,16 +1263,28 @@ pub mod kv {
         }
     }
 
-    pub fn delete_keys(keys: &[String]) {
+    pub fn delete_keys(keys: &[String], yes: bool) {
         let Some(ctx) = resolve_active_context(true, false) else {
             return;
         };
 
-        let mut deleted = 0usize;
-
-        for key in keys {
-            let full_key = prefix_key(&ctx, key);
-
+        let Some(keys) = expand_keys(&ctx, keys) else {
+            return;
+        };
+        print_key_list(
+            &format!("Keys to delete ({}):", describe_kv_context(&ctx)),
+            &keys,
+        );
+        if !confirm_destructive(&ctx, &format!("Delete {}?", count_keys(keys.len())), yes) {
+            println!("No keys deleted.");
+            return;
+        }
+
+        let mut deleted = 0usize;
+
+        for key in &keys {
+            let full_key = prefix_key(&ctx, key);
+
             match delete_entry(&ctx, &full_key) {
                 Ok(_) => {
                     deleted += 1;
@@ -1023,13 +1299,59 @@ pub mod kv {
         }
     }
 
-    pub fn export_entries(file: &Path) {
+    /// Output formats supported by `azac export`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+    pub enum ExportFormat {
+        /// azac YAML schema, suitable for `plan` and `import`
+        Yaml,
+        /// dotenv file with `__` separated keys
+        Env,
+        /// .NET appsettings.json with nested sections
+        Dotnet,
+        /// Flat JSON object keyed by configuration key
+        Json,
+        /// Kubernetes ConfigMap manifest
+        K8sConfigmap,
+        /// Kubernetes Secret manifest
+        K8sSecret,
+    }
+
+    /// What to do when a secret about to be written is also referenced by other labels or apps.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+    pub enum SharedSecretPolicy {
+        /// Ask how to proceed
+        #[default]
+        Prompt,
+        /// Write a secret scoped to the active label instead
+        Scope,
+        /// Leave the shared secret untouched
+        Refuse,
+        /// Overwrite the shared secret
+        Overwrite,
+    }
+
+    impl ExportFormat {
+        fn label(self) -> &'static str {
+            match self {
+                ExportFormat::Yaml => "YAML",
+                ExportFormat::Env => "dotenv",
+                ExportFormat::Dotnet => "appsettings.json",
+                ExportFormat::Json => "JSON",
+                ExportFormat::K8sConfigmap => "Kubernetes ConfigMap",
+                ExportFormat::K8sSecret => "Kubernetes Secret",
+            }
+        }
+    }
+
+    pub fn export_entries(file: &Path, format: ExportFormat, secrets: SecretExport) {
         let Some(ctx) = resolve_active_context(true, false) else {
             return;
         };
 
+        let to_stdout = file == Path::new("-");
+
         let spinner = create_spinner("Fetching configuration entries...");
-        let snapshots = match collect_export_snapshots(&ctx) {
+        let mut snapshots = match collect_export_snapshots(&ctx, secrets == SecretExport::Value) {
             Ok(entries) => entries,
             Err(err) => {
                 spinner.finish_and_clear();
@@ -1038,6 +1360,28 @@ pub mod kv {
             }
         };
 
+        let omitted = if secrets == SecretExport::Omit {
+            let before = snapshots.len();
+            snapshots.retain(|_, entry| entry.value_type != EntryValueType::KeyVault);
+            before - snapshots.len()
+        } else {
+            0
+        };
+
+        let unresolved: Vec<&str> = snapshots
+            .iter()
+            .filter(|(_, entry)| secrets == SecretExport::Value && !entry.value_known)
+            .map(|(key, _)| key.as_str())
+            .collect();
+        if !unresolved.is_empty() {
+            spinner.suspend(|| {
+                eprintln!(
+                    "Could not resolve values for: {}; exported their Key Vault references instead.",
+                    unresolved.join(", ")
+                )
+            });
+        }
+
         let total = snapshots.len();
         let keyvault_count = snapshots
             .values()
@@ -1047,10 +1391,7 @@ pub mod kv {
 
         spinner.set_message("Preparing export payload...");
 
-        let serialized = serde_yaml::to_string(&build_export_payload(&snapshots))
-            .map_err(|err| format!("Failed to serialize YAML: {err}"));
-
-        let data = match serialized {
+        let data = match render_export(&ctx, &snapshots, format, secrets) {
             Ok(data) => data,
             Err(err) => {
                 spinner.finish_and_clear();
@@ -1059,6 +1400,15 @@ pub mod kv {
             }
         };
 
+        if to_stdout {
+            spinner.finish_and_clear();
+            print!("{data}");
+            if omitted > 0 {
+                eprintln!("Omitted {} Key Vault {}.", omitted, plural_entries(omitted));
+            }
+            return;
+        }
+
         if let Err(err) = fs::write(file, data.as_bytes()) {
             spinner.finish_and_clear();
             eprintln!("Failed to write {}: {}", file.display(), err);
@@ -1078,587 +1428,3016 @@ pub mod kv {
             );
         } else {
             println!(
-                "Exported {} entries (plain {}, keyvault {}) as YAML → '{}'.",
+                "Exported {} entries (plain {}, keyvault {}) as {} → '{}'.",
                 total,
                 plain_count,
                 keyvault_count,
+                format.label(),
                 file.display()
             );
         }
-    }
-
-    pub fn plan(file: &Path) {
-        let Some(ctx) = resolve_active_context(true, false) else {
-            return;
-        };
-
-        let spinner = create_spinner("Fetching configuration entries...");
-        let live_entries = match collect_export_snapshots(&ctx) {
-            Ok(entries) => entries,
-            Err(err) => {
-                spinner.finish_and_clear();
-                eprintln!("Failed to build plan: {err}");
-                return;
-            }
-        };
-        spinner.finish_and_clear();
 
-        let Some(file_entries) = parse_import_map(file) else {
-            return;
-        };
+        if omitted > 0 {
+            println!("Omitted {} Key Vault {}.", omitted, plural_entries(omitted));
+        }
+    }
 
-        let report = build_plan_report(&ctx, &live_entries, file_entries);
-        print_plan_report(&ctx, &report, file);
+    fn plural_entries(count: usize) -> &'static str {
+        if count == 1 { "entry" } else { "entries" }
     }
 
-    fn build_plan_report(
+    fn render_export(
         ctx: &ActiveKvContext,
-        live_entries: &BTreeMap<String, EntrySnapshot>,
-        file_entries: Vec<ImportEntry>,
-    ) -> PlanReport {
-        let mut report = PlanReport::default();
+        snapshots: &BTreeMap<String, EntrySnapshot>,
+        format: ExportFormat,
+        secrets: SecretExport,
+    ) -> Result<String, String> {
+        let values: BTreeMap<String, String> = snapshots
+            .iter()
+            .map(|(key, entry)| (key.clone(), export_value(entry, secrets)))
+            .collect();
 
-        for entry in file_entries {
-            if entry.value_type == EntryValueType::Prompt {
-                continue;
+        let rendered = match format {
+            ExportFormat::Yaml => {
+                return serde_yaml::to_string(&build_export_payload(snapshots, secrets))
+                    .map_err(|err| format!("Failed to serialize YAML: {err}"));
             }
+            ExportFormat::Env => Ok(convert::render_env(&values, &ctx.separator)),
+            ExportFormat::Dotnet => convert::render_dotnet(&values, &ctx.separator),
+            ExportFormat::Json => convert::render_json(&values),
+            ExportFormat::K8sConfigmap => convert::render_k8s(
+                K8sKind::ConfigMap,
+                &manifest_name(ctx),
+                &values,
+                &ctx.separator,
+            ),
+            ExportFormat::K8sSecret => convert::render_k8s(
+                K8sKind::Secret,
+                &manifest_name(ctx),
+                &values,
+                &ctx.separator,
+            ),
+        };
 
-            if let Some(snapshot) = live_entries.get(&entry.key) {
-                plan_existing_entry(ctx, &entry, snapshot, &mut report);
-            } else {
-                let full_key = prefix_key(ctx, &entry.key);
-                plan_new_entry(ctx, &entry, &full_key, &mut report);
-            }
+        rendered.map_err(|err| err.to_string())
+    }
+
+    fn export_value(entry: &EntrySnapshot, secrets: SecretExport) -> String {
+        match (&entry.secret_uri, secrets) {
+            (Some(uri), SecretExport::Reference) => display_secret_reference(uri),
+            (Some(uri), _) if !entry.value_known => display_secret_reference(uri),
+            _ => entry.value.clone(),
         }
+    }
 
-        report
+    fn manifest_name(ctx: &ActiveKvContext) -> String {
+        let name = ctx
+            .app_name
+            .as_deref()
+            .map(|app| app.to_kebab_case())
+            .filter(|name| !name.is_empty());
+        name.unwrap_or_else(|| "azac-config".to_string())
     }
 
-    fn collect_export_snapshots(
-        ctx: &ActiveKvContext,
-    ) -> AzCliResult<BTreeMap<String, EntrySnapshot>> {
-        let entries = fetch_entries(ctx)?;
-        let mut snapshots = BTreeMap::new();
+    /// Shows or updates how Key Vault secret names are derived for the active context.
+    pub fn configure_secret_naming(
+        template: Option<String>,
+        separator: Option<String>,
+        casing: Option<SecretCasing>,
+        preview: Option<&str>,
+    ) {
+        let Some((store, mut context)) = super::load_context() else {
+            return;
+        };
 
-        for entry in entries {
-            let key = strip_prefix(ctx, &entry.key);
-            let (value, from_keyvault) = resolve_value(&entry, true, false);
-            let value_type = if from_keyvault {
-                EntryValueType::KeyVault
-            } else {
-                EntryValueType::Plain
-            };
-            let secret_uri = keyvault_uri_from_entry(&entry);
-            let value_known = if from_keyvault {
-                match secret_uri.as_ref() {
-                    Some(uri) => value != *uri,
-                    None => false,
-                }
-            } else {
-                true
-            };
-            snapshots.insert(
-                key,
-                EntrySnapshot {
-                    value,
-                    value_type,
-                    secret_uri,
-                    value_known,
-                },
-            );
-        }
+        let Some(active) = context.active.clone() else {
+            super::missing_setup_message();
+            return;
+        };
 
-        Ok(snapshots)
-    }
+        let changed = template.is_some() || separator.is_some() || casing.is_some();
+        let mut naming = active.secret_naming.clone();
+        if let Some(template) = template {
+            naming.template = template;
+        }
+        if let Some(separator) = separator {
+            naming.separator = separator;
+        }
+        if let Some(casing) = casing {
+            naming.casing = casing;
+        }
 
-    fn build_export_payload(entries: &BTreeMap<String, EntrySnapshot>) -> serde_json::Value {
-        let mut map = serde_json::Map::new();
+        if let Err(err) = naming.validate() {
+            eprintln!("{err}");
+            return;
+        }
 
-        for (key, entry) in entries {
-            let mut obj = serde_json::Map::new();
-            obj.insert(
-                "type".to_string(),
-                serde_json::Value::String(match entry.value_type {
-                    EntryValueType::KeyVault => "keyvault".to_string(),
-                    _ => "plain".to_string(),
-                }),
-            );
-            obj.insert(
-                "value".to_string(),
-                serde_json::Value::String(entry.value.clone()),
+        if changed {
+            if let Some(current) = context.active.as_mut() {
+                current.secret_naming = naming.clone();
+            }
+            if !super::save_context(&store, &context) {
+                return;
+            }
+            println!(
+                "Updated Key Vault secret naming for '{}'.",
+                active.config_name
             );
-            map.insert(key.clone(), serde_json::Value::Object(obj));
         }
 
-        serde_json::Value::Object(map)
+        let casing_name = naming
+            .casing
+            .to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default();
+        println!("  template  {}", naming.template);
+        println!("  separator {}", naming.separator);
+        println!("  casing    {}", casing_name);
+
+        let sample = preview
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("ConnectionStrings{}Default", active.separator));
+        let name = naming.render(
+            active.app.name.as_deref(),
+            active
+                .app
+                .label
+                .as_deref()
+                .filter(|label| !label.is_empty()),
+            &sample,
+            &active.separator,
+        );
+        println!();
+        println!("  {} -> {}", sample, name.bold());
+        if let Err(err) = validate_secret_name(&name) {
+            eprintln!("{err}");
+        }
     }
 
-    #[derive(Default)]
-    struct PlanReport {
-        actions: Vec<PlanAction>,
+    #[derive(Debug, Deserialize)]
+    struct VaultSecret {
+        name: String,
+        #[serde(default)]
+        managed: Option<bool>,
+        #[serde(default)]
+        tags: Option<BTreeMap<String, String>>,
+        #[serde(default)]
+        attributes: SecretAttributes,
     }
 
-    struct PlanAction {
-        key: String,
-        kind: PlanActionKind,
+    #[derive(Debug, Default, Deserialize)]
+    struct SecretAttributes {
+        enabled: Option<bool>,
+        created: Option<String>,
+        expires: Option<String>,
+        #[serde(rename = "notBefore")]
+        not_before: Option<String>,
+        updated: Option<String>,
     }
 
-    #[derive(Tabled)]
-    struct PlanTableRow {
-        #[tabled(rename = "")]
-        status: String,
-        #[tabled(rename = "TYPE")]
-        value_type: String,
-        #[tabled(rename = "NAME")]
-        name: String,
-        #[tabled(rename = "PLAN")]
-        plan: String,
-        #[tabled(rename = "KEYVAULT")]
-        keyvault_ref: String,
+    /// Attributes shared by `az keyvault {secret,certificate,key} show`.
+    #[derive(Debug, Deserialize)]
+    struct ObjectDetails {
+        #[serde(rename = "contentType")]
+        content_type: Option<String>,
+        #[serde(default)]
+        tags: Option<BTreeMap<String, String>>,
+        #[serde(default)]
+        attributes: SecretAttributes,
     }
 
-    enum PlanActionKind {
-        CreatePlain,
-        UpdatePlain,
-        CreateKeyVault {
-            secret_uri: String,
-        },
-        UpdateKeyVault {
-            secret_uri: String,
-        },
-        TypeChange {
-            from_type: EntryValueType,
-            to_type: EntryValueType,
-            detail: Option<String>,
-        },
+    enum RefStatus {
+        Ok,
+        Unparseable,
+        Missing,
+        Disabled,
+        Expired(String),
+        NotYetValid(String),
+        /// Any other az failure, with its raw stderr.
+        Unknown(String),
     }
 
-    fn plan_existing_entry(
-        ctx: &ActiveKvContext,
-        entry: &ImportEntry,
-        snapshot: &EntrySnapshot,
-        report: &mut PlanReport,
-    ) {
-        match snapshot.value_type {
-            EntryValueType::KeyVault => plan_existing_keyvault(entry, snapshot, report),
-            _ => plan_existing_plain(ctx, entry, snapshot, report),
+    impl RefStatus {
+        fn label(&self) -> &'static str {
+            match self {
+                RefStatus::Ok => "ok",
+                RefStatus::Unparseable => "unparseable",
+                RefStatus::Missing => "missing",
+                RefStatus::Disabled => "disabled",
+                RefStatus::Expired(_) => "expired",
+                RefStatus::NotYetValid(_) => "not yet valid",
+                RefStatus::Unknown(_) => "unknown error",
+            }
         }
-    }
 
-    fn plan_existing_plain(
-        ctx: &ActiveKvContext,
-        entry: &ImportEntry,
-        snapshot: &EntrySnapshot,
-        report: &mut PlanReport,
-    ) {
-        if entry.value_type == EntryValueType::KeyVault {
-            let full_key = prefix_key(ctx, &entry.key);
-            let detail =
-                expected_secret_uri(ctx, &full_key).map(|uri| display_secret_reference(&uri));
-            report.actions.push(PlanAction {
-                key: entry.key.clone(),
-                kind: PlanActionKind::TypeChange {
-                    from_type: EntryValueType::Plain,
-                    to_type: EntryValueType::KeyVault,
-                    detail,
-                },
-            });
-            return;
+        fn detail(&self) -> String {
+            match self {
+                RefStatus::Expired(at) => format!("expired {at}"),
+                RefStatus::NotYetValid(at) => format!("valid from {at}"),
+                RefStatus::Unknown(stderr) => {
+                    truncate_value(stderr.lines().next().unwrap_or_default(), 60)
+                }
+                _ => String::new(),
+            }
         }
+    }
 
-        if snapshot.value != entry.value {
-            report.actions.push(PlanAction {
-                key: entry.key.clone(),
-                kind: PlanActionKind::UpdatePlain,
-            });
-        }
+    #[derive(Tabled)]
+    struct RefCheckRow {
+        #[tabled(rename = "KEY")]
+        key: String,
+        #[tabled(rename = "STATUS")]
+        status: String,
+        #[tabled(rename = "REFERENCE")]
+        reference: String,
+        #[tabled(rename = "DETAIL")]
+        detail: String,
     }
 
-    fn plan_existing_keyvault(
-        entry: &ImportEntry,
-        snapshot: &EntrySnapshot,
-        report: &mut PlanReport,
-    ) {
-        let Some(secret_uri) = snapshot.secret_uri.as_ref() else {
+    /// Resolves every Key Vault reference for the active app and label and reports the broken
+    /// ones. Exits with a non-zero status when any reference fails.
+    pub fn check_refs() {
+        let Some(ctx) = resolve_active_context(true, false) else {
             return;
         };
 
-        if entry.value_type == EntryValueType::Plain {
-            report.actions.push(PlanAction {
-                key: entry.key.clone(),
-                kind: PlanActionKind::TypeChange {
-                    from_type: EntryValueType::KeyVault,
-                    to_type: EntryValueType::Plain,
-                    detail: Some(display_secret_reference(secret_uri)),
-                },
-            });
-            return;
-        }
+        let spinner = create_spinner("Fetching configuration entries...");
+        let result = fetch_entries(&ctx);
+        spinner.finish_and_clear();
 
-        let need_update = if snapshot.value_known {
-            snapshot.value != entry.value
-        } else {
-            true
+        let entries = match result {
+            Ok(entries) => entries,
+            Err(err) => {
+                eprintln!("Failed to fetch entries: {err}");
+                process::exit(1);
+            }
         };
 
-        if need_update {
-            report.actions.push(PlanAction {
-                key: entry.key.clone(),
-                kind: PlanActionKind::UpdateKeyVault {
+        let mut results = Vec::new();
+        let mut pending = VecDeque::new();
+        for entry in &entries {
+            let key = strip_prefix(&ctx, &entry.key);
+            let value = entry.value.as_deref().unwrap_or_default();
+            let is_reference = entry
+                .content_type
+                .as_deref()
+                .is_some_and(|ct| ct.contains("keyvaultref"))
+                || value.trim_start().starts_with("@Microsoft.KeyVault(");
+
+            match keyvault_uri_from_entry(entry) {
+                Some(uri) => pending.push_back((key, uri)),
+                None if is_reference => {
+                    results.push((key, truncate_value(value, 40), RefStatus::Unparseable))
+                }
+                None => {}
+            }
+        }
+
+        let total = results.len() + pending.len();
+        if total == 0 {
+            println!("No Key Vault references found.");
+            return;
+        }
+
+        results.extend(resolve_references(
+            pending,
+            "Resolving references",
+            check_reference,
+        ));
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let failures: Vec<RefCheckRow> = results
+            .iter()
+            .filter(|(_, _, status)| !matches!(status, RefStatus::Ok))
+            .map(|(key, uri, status)| RefCheckRow {
+                key: key.clone(),
+                status: status.label().to_string(),
+                reference: display_secret_reference(uri),
+                detail: status.detail(),
+            })
+            .collect();
+
+        if failures.is_empty() {
+            println!("All {} Key Vault references resolved.", total);
+            return;
+        }
+
+        let failed = failures.len();
+        let mut table = Table::new(failures);
+        table
+            .with(Style::psql())
+            .with(BorderColor::filled(Color::FG_BRIGHT_BLACK))
+            .modify(Rows::first(), Color::BOLD | Color::FG_BRIGHT_WHITE);
+        println!("{table}");
+        for (key, _, status) in &results {
+            if let RefStatus::Unknown(stderr) = status {
+                eprintln!("\n{key}:\n{stderr}");
+            }
+        }
+        println!(
+            "{} of {} Key Vault references failed to resolve.",
+            failed, total
+        );
+        process::exit(1);
+    }
+
+    /// Runs `resolve` for every `(key, secret URI)` pair on a pool of worker threads.
+    fn resolve_references<R, F>(
+        pending: VecDeque<(String, String)>,
+        message: &str,
+        resolve: F,
+    ) -> Vec<(String, String, R)>
+    where
+        R: Send,
+        F: Fn(&str) -> R + Sync,
+    {
+        let progress = ProgressBar::new(pending.len() as u64);
+        if let Ok(style) =
+            ProgressStyle::with_template("{spinner:.green} {wide_bar:.cyan/blue} {pos}/{len} {msg}")
+        {
+            progress.set_style(style);
+        }
+        progress.set_message(message.to_string());
+        progress.enable_steady_tick(Duration::from_millis(80));
+
+        let worker_count = thread::available_parallelism()
+            .map(|value| value.get())
+            .unwrap_or(4)
+            .min(pending.len())
+            .max(1);
+        let queue = Mutex::new(pending);
+        let resolved = Mutex::new(Vec::new());
+        thread::scope(|scope| {
+            for _ in 0..worker_count {
+                scope.spawn(|| {
+                    loop {
+                        let next = queue.lock().expect("reference queue poisoned").pop_front();
+                        let Some((key, uri)) = next else {
+                            break;
+                        };
+                        let result = resolve(&uri);
+                        resolved
+                            .lock()
+                            .expect("reference results poisoned")
+                            .push((key, uri, result));
+                        progress.inc(1);
+                    }
+                });
+            }
+        });
+        progress.finish_and_clear();
+
+        resolved.into_inner().expect("reference results poisoned")
+    }
+
+    /// Classifies a reference by the az exit status and, once it resolves, by the object's
+    /// attributes. Failures other than "not found" are reported with az's own output.
+    fn check_reference(uri: &str) -> RefStatus {
+        let bundle = match fetch_object_details(uri) {
+            Ok(bundle) => bundle,
+            Err(err) if err.is_not_found() => return RefStatus::Missing,
+            Err(AzCliError::CommandFailure { stderr, .. }) => {
+                return RefStatus::Unknown(stderr.trim().to_string());
+            }
+            Err(err) => return RefStatus::Unknown(err.to_string()),
+        };
+
+        let attributes = bundle.attributes;
+        if attributes.enabled == Some(false) {
+            return RefStatus::Disabled;
+        }
+
+        let now = Utc::now();
+        if let Some(expires) = attributes.expires.as_deref()
+            && DateTime::parse_from_rfc3339(expires).is_ok_and(|at| at < now)
+        {
+            return RefStatus::Expired(expires.to_string());
+        }
+        if let Some(not_before) = attributes.not_before.as_deref()
+            && DateTime::parse_from_rfc3339(not_before).is_ok_and(|at| at > now)
+        {
+            return RefStatus::NotYetValid(not_before.to_string());
+        }
+
+        RefStatus::Ok
+    }
+
+    #[derive(Tabled)]
+    struct OrphanRow {
+        #[tabled(rename = "SECRET")]
+        name: String,
+        #[tabled(rename = "ENABLED")]
+        enabled: String,
+        #[tabled(rename = "UPDATED")]
+        updated: String,
+    }
+
+    /// Lists secrets in the configured vault that no key-value references, across every label
+    /// and app in the store, and optionally deletes them.
+    pub fn keyvault_gc(delete: bool, yes: bool) {
+        let Some(ctx) = resolve_active_context(false, false) else {
+            return;
+        };
+
+        let Some(vault_name) = active_vault_name(&ctx) else {
+            eprintln!("No Key Vault configured for the current application.");
+            return;
+        };
+
+        let Some(usage) = load_secret_usage(&ctx) else {
+            return;
+        };
+
+        let spinner = create_spinner(&format!("Listing secrets in '{vault_name}'..."));
+        let result: AzCliResult<Vec<VaultSecret>> = az([
+            "keyvault",
+            "secret",
+            "list",
+            "--vault-name",
+            &vault_name,
+            "-o",
+            "json",
+        ]);
+        spinner.finish_and_clear();
+
+        let secrets = match result {
+            Ok(secrets) => secrets,
+            Err(err) => {
+                eprintln!("Failed to list secrets in '{vault_name}': {err}");
+                return;
+            }
+        };
+
+        let referenced: BTreeSet<&str> = usage
+            .references
+            .iter()
+            .map(|user| user.secret.as_str())
+            .collect();
+        let (candidates, foreign): (Vec<VaultSecret>, Vec<VaultSecret>) = secrets
+            .into_iter()
+            .filter(|secret| !secret.managed.unwrap_or(false))
+            .partition(is_azac_secret);
+        let mut orphans: Vec<VaultSecret> = candidates
+            .into_iter()
+            .filter(|secret| {
+                let reference = format!("{}/{}", vault_name, secret.name).to_ascii_lowercase();
+                !referenced.contains(reference.as_str())
+            })
+            .collect();
+        orphans.sort_by(|a, b| a.name.cmp(&b.name));
+
+        if !foreign.is_empty() {
+            println!(
+                "Ignoring {} {} without the {}={} tag.",
+                foreign.len(),
+                if foreign.len() == 1 {
+                    "secret"
+                } else {
+                    "secrets"
+                },
+                AZAC_TAG_NAME,
+                AZAC_TAG_VALUE
+            );
+        }
+
+        if orphans.is_empty() {
+            println!(
+                "Every azac secret in '{}' is referenced by App Configuration '{}'.",
+                vault_name, ctx.config_name
+            );
+            return;
+        }
+
+        let rows: Vec<OrphanRow> = orphans
+            .iter()
+            .map(|secret| OrphanRow {
+                name: secret.name.clone(),
+                enabled: match secret.attributes.enabled {
+                    Some(false) => "no".to_string(),
+                    _ => "yes".to_string(),
+                },
+                updated: secret.attributes.updated.clone().unwrap_or_default(),
+            })
+            .collect();
+
+        let mut table = Table::new(rows);
+        table
+            .with(Style::psql())
+            .with(BorderColor::filled(Color::FG_BRIGHT_BLACK))
+            .modify(Rows::first(), Color::BOLD | Color::FG_BRIGHT_WHITE);
+        println!("{table}");
+        println!(
+            "{} unreferenced {} in '{}'.",
+            orphans.len(),
+            if orphans.len() == 1 {
+                "secret"
+            } else {
+                "secrets"
+            },
+            vault_name
+        );
+
+        if !delete {
+            println!("Run with --delete to remove them.");
+            return;
+        }
+
+        let prompt = format!(
+            "Delete {} secrets from '{}'? Other stores or apps sharing this vault may still use them.",
+            orphans.len(),
+            vault_name
+        );
+        if !yes && !confirm_action(&prompt) {
+            println!("No secrets deleted.");
+            return;
+        }
+        // The vault backs every label in the store, so protected labels guard it like a prune.
+        if !ctx.protected_labels.is_empty()
+            && !confirm_by_typing(
+                &format!(
+                    "Vault '{}' backs protected labels ({})",
+                    vault_name,
+                    ctx.protected_labels.join(", ")
+                ),
+                &vault_name,
+            )
+        {
+            println!("No secrets deleted.");
+            return;
+        }
+
+        let mut deleted = 0usize;
+        for secret in &orphans {
+            let result: AzCliResult<serde_json::Value> = az([
+                "keyvault",
+                "secret",
+                "delete",
+                "--vault-name",
+                &vault_name,
+                "--name",
+                &secret.name,
+                "-o",
+                "json",
+            ]);
+            match result {
+                Ok(_) => {
+                    deleted += 1;
+                    println!("Deleted secret '{}'.", secret.name);
+                }
+                Err(err) => eprintln!("Failed to delete secret '{}': {err}", secret.name),
+            }
+        }
+
+        println!(
+            "Deleted {} of {} secrets from '{}'.",
+            deleted,
+            orphans.len(),
+            vault_name
+        );
+    }
+
+    /// Whether azac created `secret`, i.e. it carries the azac tag.
+    fn is_azac_secret(secret: &VaultSecret) -> bool {
+        secret
+            .tags
+            .as_ref()
+            .and_then(|tags| tags.get(AZAC_TAG_NAME))
+            .is_some_and(|value| value == AZAC_TAG_VALUE)
+    }
+
+    #[derive(Tabled)]
+    struct VersionRow {
+        #[tabled(rename = "")]
+        marker: String,
+        #[tabled(rename = "VERSION")]
+        version: String,
+        #[tabled(rename = "CREATED")]
+        created: String,
+        #[tabled(rename = "ENABLED")]
+        enabled: String,
+        #[tabled(rename = "EXPIRES")]
+        expires: String,
+    }
+
+    /// Lists the versions of the secret behind `key`, marking the one its reference resolves to.
+    pub fn secret_versions(key: &str) {
+        let Some(ctx) = resolve_active_context(true, false) else {
+            return;
+        };
+        let Some((_, secret_uri)) = keyvault_entry_for(&ctx, key) else {
+            return;
+        };
+        let Some(mut versions) = fetch_secret_versions(&secret_uri) else {
+            return;
+        };
+
+        if versions.is_empty() {
+            println!(
+                "Secret '{}' has no versions.",
+                display_secret_reference(&secret_uri)
+            );
+            return;
+        }
+
+        versions.sort_by(|a, b| b.attributes.created.cmp(&a.attributes.created));
+        let pinned = parse_secret_version(&secret_uri);
+        let current = pinned.clone().or_else(|| {
+            versions
+                .iter()
+                .find(|version| version.attributes.enabled != Some(false))
+                .and_then(|version| parse_secret_version(&version.id))
+        });
+
+        let rows: Vec<VersionRow> = versions
+            .iter()
+            .map(|version| {
+                let id = parse_secret_version(&version.id).unwrap_or_default();
+                let marker = if current.as_deref() == Some(id.as_str()) {
+                    if pinned.is_some() {
+                        "pinned"
+                    } else {
+                        "current"
+                    }
+                } else {
+                    ""
+                };
+                VersionRow {
+                    marker: marker.to_string(),
+                    version: id,
+                    created: version.attributes.created.clone().unwrap_or_default(),
+                    enabled: match version.attributes.enabled {
+                        Some(false) => "no".to_string(),
+                        _ => "yes".to_string(),
+                    },
+                    expires: version.attributes.expires.clone().unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        let mut table = Table::new(rows);
+        table
+            .with(Style::psql())
+            .with(BorderColor::filled(Color::FG_BRIGHT_BLACK))
+            .modify(Rows::first(), Color::BOLD | Color::FG_BRIGHT_WHITE);
+        println!("{table}");
+    }
+
+    /// Pins the reference of `key` to `version`, or to the latest version when none is given.
+    pub fn pin_secret(key: &str, version: Option<&str>) {
+        let Some(ctx) = resolve_active_context(true, false) else {
+            return;
+        };
+        let Some((full_key, secret_uri)) = keyvault_entry_for(&ctx, key) else {
+            return;
+        };
+        let Some(unpinned) = secret_uri_with_version(&secret_uri, None) else {
+            eprintln!("Key '{}' has an unrecognized Key Vault reference.", key);
+            return;
+        };
+
+        // Key Vault version ids are alphanumeric; anything else cannot name a version.
+        let lookup = match version {
+            Some(version)
+                if version.is_empty() || !version.chars().all(|c| c.is_ascii_alphanumeric()) =>
+            {
+                None
+            }
+            Some(version) => secret_uri_with_version(&unpinned, Some(version)),
+            None => Some(unpinned),
+        };
+        let Some(lookup) = lookup else {
+            report_version_not_found(key, version);
+        };
+        let resolved: AzCliResult<SecretVersion> =
+            az(["keyvault", "secret", "show", "--id", &lookup, "-o", "json"]);
+        let pinned = match resolved {
+            Ok(secret) if !secret.id.is_empty() => secret.id,
+            Ok(_) => report_version_not_found(key, version),
+            Err(err) if err.is_not_found() => report_version_not_found(key, version),
+            Err(err) => {
+                eprintln!("Failed to resolve secret version for '{}': {err}", key);
+                process::exit(1);
+            }
+        };
+
+        if pinned == secret_uri {
+            println!(
+                "Key '{}' is already pinned to '{}'.",
+                key,
+                display_secret_reference(&pinned)
+            );
+            return;
+        }
+
+        match write_keyvault_entry(&ctx, &full_key, &pinned) {
+            Ok(_) => println!(
+                "Pinned key '{}' to '{}'.",
+                key,
+                display_secret_reference(&pinned)
+            ),
+            Err(err) => eprintln!("Failed to pin key '{}': {err}", key),
+        }
+    }
+
+    fn report_version_not_found(key: &str, version: Option<&str>) -> ! {
+        match version {
+            Some(version) => eprintln!(
+                "Version '{}' of the secret behind '{}' was not found.",
+                version, key
+            ),
+            None => eprintln!("The secret behind '{}' was not found.", key),
+        }
+        process::exit(1);
+    }
+
+    /// Points the reference of `key` back at the latest secret version.
+    pub fn unpin_secret(key: &str) {
+        let Some(ctx) = resolve_active_context(true, false) else {
+            return;
+        };
+        let Some((full_key, secret_uri)) = keyvault_entry_for(&ctx, key) else {
+            return;
+        };
+
+        if parse_secret_version(&secret_uri).is_none() {
+            println!("Key '{}' already follows the latest secret version.", key);
+            return;
+        }
+
+        let Some(unpinned) = secret_uri_with_version(&secret_uri, None) else {
+            eprintln!("Key '{}' has an unrecognized Key Vault reference.", key);
+            return;
+        };
+
+        match write_keyvault_entry(&ctx, &full_key, &unpinned) {
+            Ok(_) => println!(
+                "Key '{}' now follows the latest version of '{}'.",
+                key,
+                display_secret_reference(&unpinned)
+            ),
+            Err(err) => eprintln!("Failed to unpin key '{}': {err}", key),
+        }
+    }
+
+    /// Writes a new secret version for `key` and moves a pinned reference along with it.
+    pub fn roll_secret_key(key: &str, value: &str) {
+        let Some(ctx) = resolve_active_context(true, false) else {
+            return;
+        };
+        let Some((full_key, secret_uri)) = keyvault_entry_for(&ctx, key) else {
+            return;
+        };
+
+        match roll_secret(&ctx, &full_key, &secret_uri, value) {
+            Ok(reference) if reference != secret_uri => println!(
+                "Rolled key '{}' to '{}'.",
+                key,
+                display_secret_reference(&reference)
+            ),
+            Ok(reference) => println!(
+                "Wrote a new version of '{}' for key '{}'.",
+                display_secret_reference(&reference),
+                key
+            ),
+            Err(err) => eprintln!("Failed to roll secret for '{}': {err}", key),
+        }
+    }
+
+    #[derive(Tabled)]
+    struct ExpiryRow {
+        #[tabled(rename = "KEY")]
+        key: String,
+        #[tabled(rename = "SECRET")]
+        secret: String,
+        #[tabled(rename = "EXPIRES")]
+        expires: String,
+        #[tabled(rename = "IN")]
+        remaining: String,
+        #[tabled(rename = "ENABLED")]
+        enabled: String,
+        #[tabled(rename = "UPDATED")]
+        updated: String,
+    }
+
+    /// Lists the active app's secrets that expire within `days` days, soonest first.
+    pub fn secrets_report(days: i64) {
+        let Some(ctx) = resolve_active_context(true, false) else {
+            return;
+        };
+
+        let spinner = create_spinner("Fetching configuration entries...");
+        let result = fetch_entries(&ctx);
+        spinner.finish_and_clear();
+
+        let entries = match result {
+            Ok(entries) => entries,
+            Err(err) => {
+                eprintln!("Failed to fetch entries: {err}");
+                return;
+            }
+        };
+
+        let pending: VecDeque<(String, String)> = entries
+            .iter()
+            .filter_map(|entry| {
+                keyvault_uri_from_entry(entry).map(|uri| (strip_prefix(&ctx, &entry.key), uri))
+            })
+            .collect();
+        if pending.is_empty() {
+            println!("No Key Vault references found.");
+            return;
+        }
+
+        let total = pending.len();
+        let results = resolve_references(pending, "Reading secret metadata", fetch_object_details);
+
+        let mut failed = 0usize;
+        let mut without_expiry = 0usize;
+        let mut expiring = Vec::new();
+        for (key, uri, result) in results {
+            let secret = match result {
+                Ok(secret) => secret,
+                Err(err) => {
+                    failed += 1;
+                    eprintln!("Failed to read secret for '{}': {err}", key);
+                    continue;
+                }
+            };
+
+            let Some(expires) = secret.attributes.expires.clone() else {
+                without_expiry += 1;
+                continue;
+            };
+            let Some(remaining) = days_until(&expires) else {
+                continue;
+            };
+            if remaining <= days {
+                expiring.push((remaining, key, uri, expires, secret.attributes));
+            }
+        }
+        expiring.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+        if expiring.is_empty() {
+            println!(
+                "No secrets expire within {} days ({} checked, {} without expiry).",
+                days, total, without_expiry
+            );
+            return;
+        }
+
+        let count = expiring.len();
+        let rows: Vec<ExpiryRow> = expiring
+            .into_iter()
+            .map(|(remaining, key, uri, expires, attributes)| ExpiryRow {
+                key,
+                secret: display_secret_reference(&uri),
+                expires,
+                remaining: if remaining < 0 {
+                    "expired".to_string()
+                } else {
+                    format!("{remaining}d")
+                },
+                enabled: match attributes.enabled {
+                    Some(false) => "no".to_string(),
+                    _ => "yes".to_string(),
+                },
+                updated: attributes.updated.unwrap_or_default(),
+            })
+            .collect();
+
+        let mut table = Table::new(rows);
+        table
+            .with(Style::psql())
+            .with(BorderColor::filled(Color::FG_BRIGHT_BLACK))
+            .modify(Rows::first(), Color::BOLD | Color::FG_BRIGHT_WHITE);
+        println!("{table}");
+        println!(
+            "{} of {} secrets expire within {} days ({} without expiry{}).",
+            count,
+            total,
+            days,
+            without_expiry,
+            if failed > 0 {
+                format!(", {failed} unreadable")
+            } else {
+                String::new()
+            }
+        );
+    }
+
+    /// Replaces the secret behind `key` with a generated value. The previous version is disabled,
+    /// or kept enabled until the grace period ends and disabled by a later rotate.
+    pub fn rotate_key(key: &str, policy: &RotationPolicy) {
+        let Some(ctx) = resolve_active_context(true, false) else {
+            return;
+        };
+        let Some((full_key, secret_uri)) = keyvault_entry_for(&ctx, key) else {
+            return;
+        };
+
+        let value = match rotate::generate(policy) {
+            Ok(value) => value,
+            Err(err) => {
+                eprintln!("{err}");
+                return;
+            }
+        };
+
+        retire_expired_versions(&secret_uri);
+
+        let current: AzCliResult<SecretVersion> = az([
+            "keyvault",
+            "secret",
+            "show",
+            "--id",
+            &secret_uri,
+            "-o",
+            "json",
+        ]);
+        let previous = current.ok();
+
+        let spinner = create_spinner(&format!("Rotating '{}'...", key));
+        let result = roll_secret(&ctx, &full_key, &secret_uri, &value);
+        spinner.finish_and_clear();
+
+        let reference = match result {
+            Ok(reference) => reference,
+            Err(err) => {
+                eprintln!("Failed to rotate secret for '{}': {err}", key);
+                return;
+            }
+        };
+        println!(
+            "Rotated key '{}' ({}).",
+            key,
+            display_secret_reference(&reference)
+        );
+
+        let Some(previous) = previous else {
+            return;
+        };
+        let previous_display = short_version(parse_secret_version(&previous.id).as_deref());
+
+        let (args, outcome) = match policy.grace {
+            Some(grace) => {
+                let expires = (Utc::now() + grace).to_rfc3339_opts(SecondsFormat::Secs, true);
+                let mut tags = previous.tags.unwrap_or_default();
+                tags.insert(AZAC_RETIRE_TAG.to_string(), expires.clone());
+                let mut args = vec!["--expires".to_string(), expires.clone()];
+                args.push("--tags".to_string());
+                args.extend(tags.iter().map(|(name, value)| format!("{name}={value}")));
+                (
+                    args,
+                    format!(
+                        "Previous version {previous_display} stays readable until {expires}; \
+                         the next `azac rotate {key}` after that disables it."
+                    ),
+                )
+            }
+            None => (
+                vec!["--enabled".to_string(), "false".to_string()],
+                format!("Disabled previous version {previous_display}."),
+            ),
+        };
+
+        let mut command = vec![
+            "keyvault".to_string(),
+            "secret".to_string(),
+            "set-attributes".to_string(),
+            "--id".to_string(),
+            previous.id,
+        ];
+        command.extend(args);
+        command.extend(["-o".to_string(), "json".to_string()]);
+
+        let result: AzCliResult<serde_json::Value> = az(command);
+        match result {
+            Ok(_) => println!("{outcome}"),
+            Err(err) => eprintln!(
+                "Failed to update previous version {}: {err}",
+                previous_display
+            ),
+        }
+    }
+
+    /// Looks up `key` and returns its full key and secret URI when it is a Key Vault reference.
+    fn keyvault_entry_for(ctx: &ActiveKvContext, key: &str) -> Option<(String, String)> {
+        let full_key = prefix_key(ctx, key);
+        let entry = match show_entry(ctx, &full_key) {
+            Ok(entry) => entry,
+            Err(err) => {
+                eprintln!("Failed to fetch key '{}': {err}", key);
+                return None;
+            }
+        };
+
+        match keyvault_reference_from_entry(&entry) {
+            Some(reference) if reference.is_secret() => Some((full_key, reference.uri())),
+            Some(reference) => {
+                eprintln!(
+                    "Key '{}' references a Key Vault {}; only secret references are supported.",
+                    key,
+                    reference.kind.label()
+                );
+                None
+            }
+            None => {
+                eprintln!("Key '{}' is not a Key Vault reference.", key);
+                None
+            }
+        }
+    }
+
+    /// Disables versions of the secret behind `secret_uri` whose rotation grace period has ended.
+    fn retire_expired_versions(secret_uri: &str) {
+        let Some(versions) = fetch_secret_versions(secret_uri) else {
+            return;
+        };
+
+        let now = Utc::now();
+        for version in versions {
+            let due = version
+                .tags
+                .as_ref()
+                .and_then(|tags| tags.get(AZAC_RETIRE_TAG))
+                .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                .is_some_and(|at| at <= now);
+            if !due || version.attributes.enabled == Some(false) {
+                continue;
+            }
+
+            let result: AzCliResult<serde_json::Value> = az([
+                "keyvault",
+                "secret",
+                "set-attributes",
+                "--id",
+                &version.id,
+                "--enabled",
+                "false",
+                "-o",
+                "json",
+            ]);
+            match result {
+                Ok(_) => println!(
+                    "Disabled version {} after its grace period.",
+                    display_secret_reference(&version.id)
+                ),
+                Err(err) => eprintln!(
+                    "Failed to disable version {}: {err}",
+                    display_secret_reference(&version.id)
+                ),
+            }
+        }
+    }
+
+    fn fetch_secret_versions(secret_uri: &str) -> Option<Vec<SecretVersion>> {
+        let Some((vault_name, secret_name)) = parse_secret_uri(secret_uri) else {
+            eprintln!("Invalid Key Vault secret URI: {secret_uri}");
+            return None;
+        };
+
+        let spinner = create_spinner("Fetching secret versions...");
+        let result = az([
+            "keyvault",
+            "secret",
+            "list-versions",
+            "--vault-name",
+            &vault_name,
+            "--name",
+            &secret_name,
+            "-o",
+            "json",
+        ]);
+        spinner.finish_and_clear();
+
+        match result {
+            Ok(versions) => Some(versions),
+            Err(err) => {
+                eprintln!("Failed to list versions of '{}': {err}", secret_name);
+                None
+            }
+        }
+    }
+
+    fn active_vault_name(ctx: &ActiveKvContext) -> Option<String> {
+        let base = ensure_vault_base(ctx)?;
+        KeyVaultReference::new(&base, "-", None).map(|reference| reference.vault().to_string())
+    }
+
+    pub fn run_with_env(command: &[String], naming: EnvNaming) {
+        let Some((program, args)) = command.split_first() else {
+            eprintln!("No command given. Usage: azac run -- <command> [args...]");
+            return;
+        };
+
+        let Some(vars) = collect_env_vars(naming, true) else {
+            return;
+        };
+
+        let status = Command::new(program).args(args).envs(&vars).status();
+
+        match status {
+            Ok(status) => process::exit(status.code().unwrap_or(1)),
+            Err(err) => {
+                eprintln!("Failed to start '{}': {}", program, err);
+                process::exit(1);
+            }
+        }
+    }
+
+    pub fn print_env(shell: Shell, naming: EnvNaming, include_secrets: bool) {
+        let Some(vars) = collect_env_vars(naming, include_secrets) else {
+            return;
+        };
+
+        print!("{}", convert::render_shell(&vars, shell));
+    }
+
+    /// Fetches the active app's entries keyed by environment variable name. Secrets are
+    /// resolved when requested and any that cannot be read abort the operation.
+    fn collect_env_vars(
+        naming: EnvNaming,
+        resolve_secrets: bool,
+    ) -> Option<BTreeMap<String, String>> {
+        let ctx = resolve_active_context(true, false)?;
+
+        let spinner = create_spinner("Fetching configuration entries...");
+        let snapshots = match collect_export_snapshots(&ctx, resolve_secrets) {
+            Ok(entries) => entries,
+            Err(err) => {
+                spinner.finish_and_clear();
+                eprintln!("Failed to fetch entries: {err}");
+                return None;
+            }
+        };
+        spinner.finish_and_clear();
+
+        let unresolved: Vec<&str> = snapshots
+            .iter()
+            .filter(|(_, entry)| resolve_secrets && !entry.value_known)
+            .map(|(key, _)| key.as_str())
+            .collect();
+        if !unresolved.is_empty() {
+            eprintln!(
+                "Could not resolve Key Vault secrets for: {}",
+                unresolved.join(", ")
+            );
+            return None;
+        }
+
+        let values: BTreeMap<String, String> = snapshots
+            .into_iter()
+            .filter(|(_, entry)| resolve_secrets || entry.value_type != EntryValueType::KeyVault)
+            .map(|(key, entry)| (key, entry.value))
+            .collect();
+
+        Some(convert::env_vars(&values, &ctx.separator, naming))
+    }
+
+    pub fn plan(file: &Path, source: &SourceOptions) {
+        let Some(ctx) = resolve_active_context(true, false) else {
+            return;
+        };
+
+        let spinner = create_spinner("Fetching configuration entries...");
+        let live_entries = match collect_export_snapshots(&ctx, true) {
+            Ok(entries) => entries,
+            Err(err) => {
+                spinner.finish_and_clear();
+                eprintln!("Failed to build plan: {err}");
+                return;
+            }
+        };
+        spinner.finish_and_clear();
+
+        let Some(file_entries) = load_import_source(&ctx, file, source) else {
+            return;
+        };
+
+        let report = build_plan_report(&ctx, &live_entries, file_entries);
+        print_plan_report(&ctx, &report, file);
+    }
+
+    fn build_plan_report(
+        ctx: &ActiveKvContext,
+        live_entries: &BTreeMap<String, EntrySnapshot>,
+        file_entries: Vec<ImportEntry>,
+    ) -> PlanReport {
+        let mut report = PlanReport::default();
+
+        for entry in file_entries {
+            if entry.value_type == EntryValueType::Prompt {
+                continue;
+            }
+
+            if let Some(snapshot) = live_entries.get(&entry.key) {
+                plan_existing_entry(ctx, &entry, snapshot, &mut report);
+            } else {
+                let full_key = prefix_key(ctx, &entry.key);
+                plan_new_entry(ctx, &entry, &full_key, &mut report);
+            }
+        }
+
+        let existing = live_entries
+            .iter()
+            .filter_map(|(key, snapshot)| snapshot.secret_uri.clone().map(|uri| (key.clone(), uri)))
+            .collect();
+        let planned: Vec<(String, String)> = report
+            .actions
+            .iter()
+            .filter_map(|action| match &action.kind {
+                PlanActionKind::CreateKeyVault { secret_uri }
+                | PlanActionKind::UpdateKeyVault { secret_uri }
+                | PlanActionKind::RepinKeyVault { secret_uri, .. } => {
+                    Some((action.key.clone(), secret_uri.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        report.collisions = secret_collisions(existing, &planned);
+
+        report
+    }
+
+    fn collect_export_snapshots(
+        ctx: &ActiveKvContext,
+        fetch_secrets: bool,
+    ) -> AzCliResult<BTreeMap<String, EntrySnapshot>> {
+        let entries = fetch_entries(ctx)?;
+        let mut snapshots = BTreeMap::new();
+
+        for entry in entries {
+            let key = strip_prefix(ctx, &entry.key);
+            let (value, from_keyvault) = resolve_value(&entry, fetch_secrets, false);
+            let value_type = if from_keyvault {
+                EntryValueType::KeyVault
+            } else {
+                EntryValueType::Plain
+            };
+            let secret_uri = keyvault_uri_from_entry(&entry);
+            let value_known = if from_keyvault {
+                match secret_uri.as_ref() {
+                    Some(uri) => value != *uri,
+                    None => false,
+                }
+            } else {
+                true
+            };
+            snapshots.insert(
+                key,
+                EntrySnapshot {
+                    value,
+                    value_type,
+                    secret_uri,
+                    value_known,
+                },
+            );
+        }
+
+        Ok(snapshots)
+    }
+
+    fn build_export_payload(
+        entries: &BTreeMap<String, EntrySnapshot>,
+        secrets: SecretExport,
+    ) -> serde_json::Value {
+        let mut map = serde_json::Map::new();
+
+        for (key, entry) in entries {
+            let mut obj = serde_json::Map::new();
+            obj.insert(
+                "type".to_string(),
+                serde_json::Value::String(match entry.value_type {
+                    EntryValueType::KeyVault => "keyvault".to_string(),
+                    _ => "plain".to_string(),
+                }),
+            );
+            match (&entry.secret_uri, secrets) {
+                (Some(uri), _) if secrets == SecretExport::Reference || !entry.value_known => {
+                    obj.insert(
+                        "reference".to_string(),
+                        serde_json::Value::String(display_secret_reference(uri)),
+                    );
+                }
+                _ => {
+                    obj.insert(
+                        "value".to_string(),
+                        serde_json::Value::String(entry.value.clone()),
+                    );
+                }
+            }
+            map.insert(key.clone(), serde_json::Value::Object(obj));
+        }
+
+        serde_json::Value::Object(map)
+    }
+
+    #[derive(Default)]
+    struct PlanReport {
+        actions: Vec<PlanAction>,
+        collisions: Vec<(String, Vec<String>)>,
+    }
+
+    struct PlanAction {
+        key: String,
+        kind: PlanActionKind,
+    }
+
+    #[derive(Tabled)]
+    struct PlanTableRow {
+        #[tabled(rename = "")]
+        status: String,
+        #[tabled(rename = "TYPE")]
+        value_type: String,
+        #[tabled(rename = "NAME")]
+        name: String,
+        #[tabled(rename = "PLAN")]
+        plan: String,
+        #[tabled(rename = "KEYVAULT")]
+        keyvault_ref: String,
+    }
+
+    enum PlanActionKind {
+        CreatePlain,
+        UpdatePlain,
+        CreateKeyVault {
+            secret_uri: String,
+        },
+        UpdateKeyVault {
+            secret_uri: String,
+        },
+        RepinKeyVault {
+            secret_uri: String,
+            from_version: Option<String>,
+        },
+        TypeChange {
+            from_type: EntryValueType,
+            to_type: EntryValueType,
+            detail: Option<String>,
+        },
+        /// The live entry references a certificate or key, which has no value to compare
+        /// against or write.
+        Unresolvable {
+            reference: String,
+        },
+    }
+
+    fn plan_existing_entry(
+        ctx: &ActiveKvContext,
+        entry: &ImportEntry,
+        snapshot: &EntrySnapshot,
+        report: &mut PlanReport,
+    ) {
+        match snapshot.value_type {
+            EntryValueType::KeyVault => plan_existing_keyvault(entry, snapshot, report),
+            _ => plan_existing_plain(ctx, entry, snapshot, report),
+        }
+    }
+
+    fn plan_existing_plain(
+        ctx: &ActiveKvContext,
+        entry: &ImportEntry,
+        snapshot: &EntrySnapshot,
+        report: &mut PlanReport,
+    ) {
+        if entry.value_type == EntryValueType::KeyVault {
+            let full_key = prefix_key(ctx, &entry.key);
+            let detail = entry
+                .reference
+                .clone()
+                .or_else(|| expected_secret_uri(ctx, &full_key))
+                .map(|uri| display_secret_reference(&uri));
+            report.actions.push(PlanAction {
+                key: entry.key.clone(),
+                kind: PlanActionKind::TypeChange {
+                    from_type: EntryValueType::Plain,
+                    to_type: EntryValueType::KeyVault,
+                    detail,
+                },
+            });
+            return;
+        }
+
+        if snapshot.value != entry.value {
+            report.actions.push(PlanAction {
+                key: entry.key.clone(),
+                kind: PlanActionKind::UpdatePlain,
+            });
+        }
+    }
+
+    fn plan_existing_keyvault(
+        entry: &ImportEntry,
+        snapshot: &EntrySnapshot,
+        report: &mut PlanReport,
+    ) {
+        let Some(secret_uri) = snapshot.secret_uri.as_ref() else {
+            return;
+        };
+
+        if entry.value_type == EntryValueType::Plain {
+            report.actions.push(PlanAction {
+                key: entry.key.clone(),
+                kind: PlanActionKind::TypeChange {
+                    from_type: EntryValueType::KeyVault,
+                    to_type: EntryValueType::Plain,
+                    detail: Some(display_secret_reference(secret_uri)),
+                },
+            });
+            return;
+        }
+
+        // A reference-only entry relinks the key; the secret value itself is left alone.
+        if let Some(reference) = entry.reference.as_ref() {
+            let kind = if secret_identity(reference) != secret_identity(secret_uri) {
+                PlanActionKind::UpdateKeyVault {
+                    secret_uri: reference.clone(),
+                }
+            } else if parse_secret_version(reference) != parse_secret_version(secret_uri) {
+                PlanActionKind::RepinKeyVault {
+                    secret_uri: reference.clone(),
+                    from_version: parse_secret_version(secret_uri),
+                }
+            } else {
+                return;
+            };
+            report.actions.push(PlanAction {
+                key: entry.key.clone(),
+                kind,
+            });
+            return;
+        }
+
+        if reference_kind(secret_uri) != ReferenceKind::Secret {
+            report.actions.push(PlanAction {
+                key: entry.key.clone(),
+                kind: PlanActionKind::Unresolvable {
+                    reference: secret_uri.clone(),
+                },
+            });
+            return;
+        }
+
+        let need_update = if snapshot.value_known {
+            snapshot.value != entry.value
+        } else {
+            true
+        };
+
+        if need_update {
+            report.actions.push(PlanAction {
+                key: entry.key.clone(),
+                kind: PlanActionKind::UpdateKeyVault {
                     secret_uri: secret_uri.clone(),
                 },
             });
         }
     }
 
-    fn plan_new_entry(
-        ctx: &ActiveKvContext,
-        entry: &ImportEntry,
-        full_key: &str,
-        report: &mut PlanReport,
-    ) {
-        match entry.value_type {
-            EntryValueType::Plain => {
-                report.actions.push(PlanAction {
-                    key: entry.key.clone(),
-                    kind: PlanActionKind::CreatePlain,
-                });
+    fn plan_new_entry(
+        ctx: &ActiveKvContext,
+        entry: &ImportEntry,
+        full_key: &str,
+        report: &mut PlanReport,
+    ) {
+        match entry.value_type {
+            EntryValueType::Plain => {
+                report.actions.push(PlanAction {
+                    key: entry.key.clone(),
+                    kind: PlanActionKind::CreatePlain,
+                });
+            }
+            EntryValueType::KeyVault => {
+                let secret_uri = entry
+                    .reference
+                    .clone()
+                    .or_else(|| expected_secret_uri(ctx, full_key));
+                if let Some(secret_uri) = secret_uri {
+                    report.actions.push(PlanAction {
+                        key: entry.key.clone(),
+                        kind: PlanActionKind::CreateKeyVault { secret_uri },
+                    });
+                }
+            }
+            EntryValueType::Prompt => {}
+        }
+    }
+
+    fn expected_secret_uri(ctx: &ActiveKvContext, full_key: &str) -> Option<String> {
+        let vault_base = ensure_vault_base(ctx)?;
+        let secret_name = secret_name_for(ctx, full_key);
+        Some(format!("{}/secrets/{}", vault_base, secret_name))
+    }
+
+    fn short_version(version: Option<&str>) -> String {
+        match version {
+            Some(version) => version.chars().take(8).collect(),
+            None => "latest".to_string(),
+        }
+    }
+
+    fn entry_type_name(value_type: EntryValueType) -> &'static str {
+        match value_type {
+            EntryValueType::Plain => "plain",
+            EntryValueType::KeyVault => "keyvault",
+            EntryValueType::Prompt => "prompt",
+        }
+    }
+
+    fn print_plan_report(ctx: &ActiveKvContext, report: &PlanReport, file: &Path) {
+        println!("Planning to update:");
+        println!(
+            "  app {}",
+            ctx.app_name
+                .as_deref()
+                .filter(|name| !name.is_empty())
+                .unwrap_or("none")
+        );
+        println!(
+            "  label {}",
+            ctx.label
+                .as_deref()
+                .filter(|label| !label.is_empty())
+                .unwrap_or("none")
+        );
+        println!("  config {}", ctx.config_name);
+        println!(
+            "  keyvault {}",
+            ctx.keyvault
+                .as_deref()
+                .filter(|kv| !kv.is_empty())
+                .unwrap_or("none")
+        );
+
+        if report.actions.is_empty() {
+            println!(
+                "Configuration already matches snapshot '{}'.",
+                file.display()
+            );
+            return;
+        }
+
+        let create_total = report
+            .actions
+            .iter()
+            .filter(|action| {
+                matches!(
+                    action.kind,
+                    PlanActionKind::CreatePlain | PlanActionKind::CreateKeyVault { .. }
+                )
+            })
+            .count();
+        let update_total = report
+            .actions
+            .iter()
+            .filter(|action| {
+                matches!(
+                    action.kind,
+                    PlanActionKind::UpdatePlain
+                        | PlanActionKind::UpdateKeyVault { .. }
+                        | PlanActionKind::RepinKeyVault { .. }
+                )
+            })
+            .count();
+
+        let rows: Vec<PlanTableRow> = report
+            .actions
+            .iter()
+            .map(|action| match &action.kind {
+                PlanActionKind::CreatePlain => PlanTableRow {
+                    status: "+".to_string(),
+                    value_type: "plain".to_string(),
+                    name: action.key.clone(),
+                    plan: "create".to_string(),
+                    keyvault_ref: String::new(),
+                },
+                PlanActionKind::UpdatePlain => PlanTableRow {
+                    status: "~".to_string(),
+                    value_type: "plain".to_string(),
+                    name: action.key.clone(),
+                    plan: "update".to_string(),
+                    keyvault_ref: String::new(),
+                },
+                PlanActionKind::CreateKeyVault { secret_uri } => PlanTableRow {
+                    status: "+".to_string(),
+                    value_type: "keyvault".to_string(),
+                    name: action.key.clone(),
+                    plan: "create".to_string(),
+                    keyvault_ref: display_secret_reference(secret_uri),
+                },
+                PlanActionKind::UpdateKeyVault { secret_uri } => PlanTableRow {
+                    status: "~".to_string(),
+                    value_type: "keyvault".to_string(),
+                    name: action.key.clone(),
+                    plan: "update".to_string(),
+                    keyvault_ref: display_secret_reference(secret_uri),
+                },
+                PlanActionKind::RepinKeyVault {
+                    secret_uri,
+                    from_version,
+                } => PlanTableRow {
+                    status: "~".to_string(),
+                    value_type: "keyvault".to_string(),
+                    name: action.key.clone(),
+                    plan: format!(
+                        "version {} -> {}",
+                        short_version(from_version.as_deref()),
+                        short_version(parse_secret_version(secret_uri).as_deref())
+                    ),
+                    keyvault_ref: display_secret_reference(secret_uri),
+                },
+                PlanActionKind::TypeChange {
+                    from_type,
+                    to_type,
+                    detail,
+                } => {
+                    let transition = format!(
+                        "{} -> {}",
+                        entry_type_name(*from_type),
+                        entry_type_name(*to_type)
+                    );
+                    PlanTableRow {
+                        status: "!".to_string(),
+                        value_type: transition,
+                        name: action.key.clone(),
+                        plan: "type change".to_string(),
+                        keyvault_ref: detail.clone().unwrap_or_default(),
+                    }
+                }
+                PlanActionKind::Unresolvable { reference } => PlanTableRow {
+                    status: "?".to_string(),
+                    value_type: format!("keyvault {}", reference_kind(reference).label()),
+                    name: action.key.clone(),
+                    plan: "cannot update".to_string(),
+                    keyvault_ref: display_secret_reference(reference),
+                },
+            })
+            .collect();
+
+        let mut table = Table::new(rows);
+        table.with(Style::blank());
+
+        let table_str = table.to_string();
+        println!();
+        for line in table_str.lines() {
+            println!("{}", line);
+        }
+
+        let type_change_total = report
+            .actions
+            .iter()
+            .filter(|action| matches!(action.kind, PlanActionKind::TypeChange { .. }))
+            .count();
+        let unresolvable_total = report
+            .actions
+            .iter()
+            .filter(|action| matches!(action.kind, PlanActionKind::Unresolvable { .. }))
+            .count();
+
+        println!();
+        println!("Summary:");
+        println!("  + {} to create", create_total);
+        println!("  ~ {} to update", update_total);
+        println!("  ! {} type change", type_change_total);
+        if unresolvable_total > 0 {
+            println!(
+                "  ? {} cannot be updated (certificate or key references have no value)",
+                unresolvable_total
+            );
+        }
+
+        if !report.collisions.is_empty() {
+            println!();
+            println!("Secret name collisions:");
+            for (secret, keys) in &report.collisions {
+                println!("  {} <- {}", secret, keys.join(", "));
+            }
+        }
+    }
+
+    pub fn import_entries(
+        path: &Path,
+        source: &SourceOptions,
+        shared: SharedSecretPolicy,
+        yes: bool,
+    ) {
+        let Some(ctx) = resolve_active_context(true, false) else {
+            return;
+        };
+
+        let Some(entries) = load_import_source(&ctx, path, source) else {
+            return;
+        };
+
+        let mut prepared_entries = Vec::new();
+        let mut skipped = 0usize;
+
+        for mut entry in entries {
+            if entry.value_type == EntryValueType::Prompt {
+                match prompt_value_type(&entry.key) {
+                    Some(kind) => entry.value_type = kind,
+                    None => {
+                        println!("Skipping '{}' as requested.", entry.key);
+                        skipped += 1;
+                        continue;
+                    }
+                }
+            }
+
+            prepared_entries.push(entry);
+        }
+
+        if !check_import_collisions(&ctx, &prepared_entries) {
+            return;
+        }
+
+        let Some(overwritten) = overwritten_keys(&ctx, &prepared_entries) else {
+            return;
+        };
+        if !overwritten.is_empty() {
+            print_key_list(
+                &format!(
+                    "Import will overwrite {} ({}):",
+                    count_keys(overwritten.len()),
+                    describe_kv_context(&ctx)
+                ),
+                &overwritten,
+            );
+            if !confirm_destructive(&ctx, "Overwrite these keys?", yes) {
+                println!("Nothing imported.");
+                return;
+            }
+        }
+
+        let Some(secret_targets) =
+            resolve_import_secret_targets(&ctx, &mut prepared_entries, &mut skipped, shared)
+        else {
+            return;
+        };
+
+        if prepared_entries.is_empty() {
+            if skipped > 0 {
+                println!(
+                    "No entries to import; skipped {} {} during prompting.",
+                    skipped,
+                    if skipped == 1 { "entry" } else { "entries" }
+                );
+            } else {
+                println!("No entries to import.");
+            }
+            return;
+        }
+
+        let total = prepared_entries.len();
+        let config_name = ctx.config_name.clone();
+        let ctx = Arc::new(ctx);
+        let secret_targets = Arc::new(secret_targets);
+        let queue = Arc::new(Mutex::new(VecDeque::from(prepared_entries)));
+        let successes = Arc::new(AtomicUsize::new(0));
+        let failures = Arc::new(AtomicUsize::new(0));
+
+        let multi = MultiProgress::new();
+        let summary = multi.add(ProgressBar::new(total as u64));
+        if let Ok(style) = ProgressStyle::with_template(
+            "{spinner:.green} [{elapsed_precise}] {wide_bar:.cyan/blue} {pos}/{len} {msg}",
+        ) {
+            summary.set_style(style);
+        }
+        summary.set_message("Import summary");
+        summary.enable_steady_tick(Duration::from_millis(80));
+
+        let entry_style = ProgressStyle::with_template("{spinner:.cyan} {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_spinner());
+        let entry_style = Arc::new(entry_style);
+
+        let mut worker_count = thread::available_parallelism()
+            .map(|value| value.get())
+            .unwrap_or(4);
+        worker_count = worker_count.min(total).max(1);
+
+        let mut handles = Vec::with_capacity(worker_count);
+        for _ in 0..worker_count {
+            let ctx = Arc::clone(&ctx);
+            let secret_targets = Arc::clone(&secret_targets);
+            let queue = Arc::clone(&queue);
+            let success_counter = Arc::clone(&successes);
+            let failure_counter = Arc::clone(&failures);
+            let summary_bar = summary.clone();
+            let mp = multi.clone();
+            let entry_style = Arc::clone(&entry_style);
+
+            handles.push(thread::spawn(move || {
+                loop {
+                    let entry = {
+                        let mut guard = queue.lock().expect("import queue poisoned");
+                        guard.pop_front()
+                    };
+
+                    let Some(entry) = entry else {
+                        break;
+                    };
+
+                    let spinner = mp.add(ProgressBar::new_spinner());
+                    spinner.set_style(entry_style.as_ref().clone());
+                    spinner.set_message(format!("{} {}", entry.key, entry.value_type.label()));
+                    spinner.enable_steady_tick(Duration::from_millis(80));
+
+                    if process_import_entry(ctx.as_ref(), &entry, secret_targets.as_ref()) {
+                        success_counter.fetch_add(1, Ordering::Relaxed);
+                        spinner.finish_with_message(format!(
+                            "✔ {} {}",
+                            entry.key,
+                            entry.value_type.label()
+                        ));
+                    } else {
+                        failure_counter.fetch_add(1, Ordering::Relaxed);
+                        spinner.finish_with_message(format!("✖ {} (see logs)", entry.key));
+                    }
+
+                    summary_bar.inc(1);
+                }
+            }));
+        }
+
+        for handle in handles {
+            if let Err(err) = handle.join() {
+                eprintln!("An import worker thread panicked: {err:?}");
+            }
+        }
+
+        summary.finish_with_message(format!(
+            "Imported {} of {} entries into '{}'.",
+            successes.load(Ordering::Relaxed),
+            total,
+            config_name
+        ));
+        if skipped > 0 {
+            println!(
+                "Skipped {} {} during prompting.",
+                skipped,
+                if skipped == 1 { "entry" } else { "entries" }
+            );
+        }
+
+        let failed = failures.load(Ordering::Relaxed);
+        if failed > 0 {
+            eprintln!(
+                "Failed to import {} {}. See logs above for details.",
+                failed,
+                if failed == 1 { "entry" } else { "entries" }
+            );
+        }
+    }
+
+    /// How `azac copy` handles Key Vault entries.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+    pub enum CopySecrets {
+        /// Point the copied entries at the same secrets
+        #[default]
+        Reference,
+        /// Copy each secret into the destination vault and reference the copy
+        Copy,
+        /// Leave Key Vault entries out of the copy
+        Skip,
+    }
+
+    /// Where `azac copy` writes entries. Unset fields keep the active context's values.
+    pub struct CopyOptions {
+        pub keys: Vec<String>,
+        pub to_context: Option<String>,
+        pub to_store: Option<String>,
+        pub to_app: Option<String>,
+        pub to_label: Option<String>,
+        pub to_keyvault: Option<String>,
+        pub secrets: CopySecrets,
+        pub shared_secrets: SharedSecretPolicy,
+        pub dry_run: bool,
+        pub yes: bool,
+    }
+
+    struct CopyAction {
+        key: String,
+        exists: bool,
+        tags: Option<BTreeMap<String, String>>,
+        write: CopyWrite,
+    }
+
+    enum CopyWrite {
+        Plain {
+            value: String,
+            content_type: Option<String>,
+        },
+        Reference {
+            secret_uri: String,
+        },
+        Secret {
+            from_uri: String,
+            to_uri: String,
+        },
+    }
+
+    /// Copies the active app's entries to another label, app prefix or store.
+    pub fn copy_entries(options: &CopyOptions) {
+        let Some(source) = resolve_active_context(true, false) else {
+            return;
+        };
+        let Some(dest) = resolve_copy_destination(&source, options) else {
+            return;
+        };
+
+        if dest.endpoint == source.endpoint
+            && dest.app_name == source.app_name
+            && dest.label == source.label
+        {
+            eprintln!(
+                "The destination is the same as the source; pass --to-label, --to-app, --to-store or --to-context."
+            );
+            return;
+        }
+
+        if options.secrets == CopySecrets::Copy && dest.keyvault.is_none() {
+            eprintln!(
+                "No Key Vault configured for the destination; pass --to-keyvault or use --secrets reference."
+            );
+            return;
+        }
+
+        let spinner = create_spinner("Fetching source entries...");
+        let source_entries = match fetch_entries(&source) {
+            Ok(entries) => entries,
+            Err(err) => {
+                spinner.finish_and_clear();
+                eprintln!("Failed to fetch entries: {err}");
+                return;
+            }
+        };
+        spinner.set_message("Fetching destination entries...");
+        let dest_entries: BTreeMap<String, KeyValue> = match fetch_entries(&dest) {
+            Ok(entries) => entries
+                .into_iter()
+                .map(|entry| (strip_prefix(&dest, &entry.key), entry))
+                .collect(),
+            Err(err) => {
+                spinner.finish_and_clear();
+                eprintln!("Failed to fetch destination entries: {err}");
+                return;
+            }
+        };
+        spinner.finish_and_clear();
+
+        let mut selected = Vec::new();
+        for entry in source_entries {
+            let key = strip_prefix(&source, &entry.key);
+            if options.keys.is_empty()
+                || options
+                    .keys
+                    .iter()
+                    .any(|pattern| key_matches(pattern, &key))
+            {
+                selected.push((key, entry));
+            }
+        }
+
+        if selected.is_empty() {
+            println!("No keys matched; nothing to copy.");
+            return;
+        }
+
+        let mut actions = Vec::new();
+        let mut unchanged = 0usize;
+        let mut skipped = 0usize;
+        for (key, entry) in selected {
+            let existing = dest_entries.get(&key);
+            let write = match keyvault_reference_from_entry(&entry) {
+                Some(_) if options.secrets == CopySecrets::Skip => {
+                    skipped += 1;
+                    continue;
+                }
+                Some(reference)
+                    if options.secrets == CopySecrets::Copy && reference.is_secret() =>
+                {
+                    let Some(to_uri) = generated_secret_uri(&dest, &prefix_key(&dest, &key)) else {
+                        return;
+                    };
+                    if secret_identity(&to_uri) == secret_identity(&reference.uri()) {
+                        // The destination name resolves to the very same secret.
+                        CopyWrite::Reference {
+                            secret_uri: reference.uri(),
+                        }
+                    } else {
+                        CopyWrite::Secret {
+                            from_uri: reference.uri(),
+                            to_uri,
+                        }
+                    }
+                }
+                Some(reference) => CopyWrite::Reference {
+                    secret_uri: reference.uri(),
+                },
+                None => CopyWrite::Plain {
+                    value: entry.value.clone().unwrap_or_default(),
+                    content_type: entry.content_type.clone(),
+                },
+            };
+
+            let is_unchanged = existing.is_some_and(|existing| {
+                let same_value = match &write {
+                    CopyWrite::Plain {
+                        value,
+                        content_type,
+                    } => {
+                        existing.value.as_deref().unwrap_or_default() == value
+                            && existing.content_type == *content_type
+                    }
+                    CopyWrite::Reference { secret_uri } => {
+                        keyvault_uri_from_entry(existing).as_deref() == Some(secret_uri.as_str())
+                    }
+                    CopyWrite::Secret { .. } => false,
+                };
+                same_value
+                    && existing.tags.clone().unwrap_or_default()
+                        == entry.tags.clone().unwrap_or_default()
+            });
+            if is_unchanged {
+                unchanged += 1;
+                continue;
+            }
+
+            actions.push(CopyAction {
+                key,
+                exists: existing.is_some(),
+                tags: entry.tags.clone(),
+                write,
+            });
+        }
+
+        print_copy_plan(&source, &dest, &actions, unchanged, skipped);
+
+        if actions.is_empty() || options.dry_run {
+            return;
+        }
+
+        let prompt = format!(
+            "Copy {} {} to {}?",
+            actions.len(),
+            plural_entries(actions.len()),
+            describe_kv_context(&dest)
+        );
+        if !confirm_destructive(&dest, &prompt, options.yes) {
+            println!("Nothing copied.");
+            return;
+        }
+
+        let Some(actions) = resolve_copy_secret_targets(&dest, actions, options.shared_secrets)
+        else {
+            return;
+        };
+        if actions.is_empty() {
+            println!("Nothing copied.");
+            return;
+        }
+
+        let progress = ProgressBar::new(actions.len() as u64);
+        if let Ok(style) = ProgressStyle::with_template(
+            "{spinner:.green} [{elapsed_precise}] {wide_bar:.cyan/blue} {pos}/{len} {msg}",
+        ) {
+            progress.set_style(style);
+        }
+        progress.enable_steady_tick(Duration::from_millis(80));
+
+        let mut copied = 0usize;
+        for action in &actions {
+            progress.set_message(action.key.clone());
+            let full_key = prefix_key(&dest, &action.key);
+            let result = progress.suspend(|| copy_entry(&dest, &full_key, action));
+            if result {
+                copied += 1;
+            }
+            progress.inc(1);
+        }
+
+        progress.finish_with_message(format!(
+            "Copied {} of {} entries to {}.",
+            copied,
+            actions.len(),
+            describe_kv_context(&dest)
+        ));
+        if copied < actions.len() {
+            eprintln!("Some entries were not copied. See logs above for details.");
+        }
+    }
+
+    fn resolve_copy_destination(
+        source: &ActiveKvContext,
+        options: &CopyOptions,
+    ) -> Option<ActiveKvContext> {
+        let mut dest = match options.to_context.as_deref() {
+            Some(name) => {
+                let (_, context) = super::load_context()?;
+                let Some(saved) = context.saved.get(name) else {
+                    eprintln!(
+                        "No saved context named '{name}'. Run `azac context save {name}` first."
+                    );
+                    return None;
+                };
+                kv_context_from(saved)
+            }
+            None => source.clone(),
+        };
+
+        if let Some(store) = options.to_store.as_deref().map(str::trim)
+            && !store.eq_ignore_ascii_case(&dest.config_name)
+        {
+            // The vault, naming template and protected labels belong to the store, so take
+            // them from a context saved for it.
+            let (_, context) = super::load_context()?;
+            let Some(saved) = context
+                .active
+                .iter()
+                .chain(context.saved.values())
+                .find(|saved| saved.config_name.eq_ignore_ascii_case(store))
+            else {
+                eprintln!(
+                    "No saved context for store '{store}'. Run `azac setup` for it and `azac context save <name>`, then copy with --to-context."
+                );
+                return None;
+            };
+            let store_ctx = kv_context_from(saved);
+            dest.config_name = store_ctx.config_name;
+            dest.endpoint = store_ctx.endpoint;
+            dest.keyvault = store_ctx.keyvault;
+            dest.secret_naming = store_ctx.secret_naming;
+            dest.protected_labels = store_ctx.protected_labels;
+        }
+        if let Some(app) = options.to_app.as_deref() {
+            dest.app_name = Some(app.trim().to_string()).filter(|app| !app.is_empty());
+        }
+        if let Some(label) = options.to_label.as_deref() {
+            dest.label = Some(label.trim().to_string()).filter(|label| !label.is_empty());
+        }
+        if let Some(keyvault) = options.to_keyvault.as_deref() {
+            dest.keyvault = Some(keyvault.trim().to_string()).filter(|kv| !kv.is_empty());
+        }
+
+        Some(dest)
+    }
+
+    /// Applies the shared-secret policy to every secret the copy would write in the destination
+    /// vault. Redirects scoped secrets and drops refused actions.
+    fn resolve_copy_secret_targets(
+        dest: &ActiveKvContext,
+        actions: Vec<CopyAction>,
+        policy: SharedSecretPolicy,
+    ) -> Option<Vec<CopyAction>> {
+        if !actions
+            .iter()
+            .any(|action| matches!(action.write, CopyWrite::Secret { .. }))
+        {
+            return Some(actions);
+        }
+
+        let usage = load_secret_usage(dest)?;
+        let mut kept = Vec::with_capacity(actions.len());
+        for mut action in actions {
+            if let CopyWrite::Secret { to_uri, .. } = &mut action.write {
+                let full_key = prefix_key(dest, &action.key);
+                match resolve_secret_target(dest, &usage, &full_key, to_uri, policy) {
+                    Some(target) => *to_uri = target,
+                    None => continue,
+                }
+            }
+            kept.push(action);
+        }
+        Some(kept)
+    }
+
+    fn copy_entry(dest: &ActiveKvContext, full_key: &str, action: &CopyAction) -> bool {
+        let tags = action.tags.as_ref();
+        let result = match &action.write {
+            CopyWrite::Plain {
+                value,
+                content_type,
+            } => write_entry_with_tags(dest, full_key, value, content_type.as_deref(), tags),
+            CopyWrite::Reference { secret_uri } => {
+                write_keyvault_entry_with_tags(dest, full_key, secret_uri, tags)
+            }
+            CopyWrite::Secret { from_uri, to_uri } => {
+                let value = match fetch_secret_value(from_uri) {
+                    Ok(value) => value,
+                    Err(err) => {
+                        eprintln!(
+                            "Failed to read secret '{}': {}",
+                            display_secret_reference(from_uri),
+                            err
+                        );
+                        return false;
+                    }
+                };
+                let Some(to_uri) = store_secret(to_uri, &value) else {
+                    return false;
+                };
+                write_keyvault_entry_with_tags(dest, full_key, &to_uri, tags)
+            }
+        };
+
+        match result {
+            Ok(_) => true,
+            Err(err) => {
+                eprintln!("Failed to copy '{}': {}", full_key, err);
+                false
+            }
+        }
+    }
+
+    fn print_copy_plan(
+        source: &ActiveKvContext,
+        dest: &ActiveKvContext,
+        actions: &[CopyAction],
+        unchanged: usize,
+        skipped: usize,
+    ) {
+        println!("Copying from {}", describe_kv_context(source));
+        println!("          to {}", describe_kv_context(dest));
+
+        if !actions.is_empty() {
+            let rows: Vec<PlanTableRow> = actions
+                .iter()
+                .map(|action| {
+                    let (status, plan) = if action.exists {
+                        ("~", "update")
+                    } else {
+                        ("+", "create")
+                    };
+                    let (value_type, plan, keyvault_ref) = match &action.write {
+                        CopyWrite::Plain { .. } => ("plain", plan.to_string(), String::new()),
+                        CopyWrite::Reference { secret_uri } => (
+                            "keyvault",
+                            plan.to_string(),
+                            display_secret_reference(secret_uri),
+                        ),
+                        CopyWrite::Secret { from_uri, to_uri } => (
+                            "keyvault",
+                            format!("{plan}, copy secret"),
+                            format!(
+                                "{} -> {}",
+                                display_secret_reference(from_uri),
+                                display_secret_reference(to_uri)
+                            ),
+                        ),
+                    };
+                    PlanTableRow {
+                        status: status.to_string(),
+                        value_type: value_type.to_string(),
+                        name: action.key.clone(),
+                        plan,
+                        keyvault_ref,
+                    }
+                })
+                .collect();
+
+            let mut table = Table::new(rows);
+            table.with(Style::blank());
+            println!();
+            println!("{table}");
+        }
+
+        let create_total = actions.iter().filter(|action| !action.exists).count();
+        println!();
+        println!("Summary:");
+        println!("  + {} to create", create_total);
+        println!("  ~ {} to update", actions.len() - create_total);
+        println!("  = {} unchanged", unchanged);
+        if skipped > 0 {
+            println!(
+                "  - {} Key Vault {} skipped",
+                skipped,
+                plural_entries(skipped)
+            );
+        }
+    }
+
+    fn describe_kv_context(ctx: &ActiveKvContext) -> String {
+        format!(
+            "config {}, app {}, label {}",
+            ctx.config_name,
+            ctx.app_name
+                .as_deref()
+                .filter(|name| !name.is_empty())
+                .unwrap_or("none"),
+            ctx.label.as_deref().unwrap_or("none")
+        )
+    }
+
+    fn is_key_pattern(key: &str) -> bool {
+        key.contains('*')
+    }
+
+    /// Resolves key arguments for the active app: exact keys pass through unchanged and
+    /// patterns are matched against the app's entries.
+    fn expand_keys(ctx: &ActiveKvContext, keys: &[String]) -> Option<Vec<String>> {
+        if !keys.iter().any(|key| is_key_pattern(key)) {
+            return Some(keys.to_vec());
+        }
+
+        let spinner = create_spinner("Fetching configuration entries...");
+        let entries = match fetch_entries(ctx) {
+            Ok(entries) => entries,
+            Err(err) => {
+                spinner.finish_and_clear();
+                eprintln!("Failed to fetch entries: {err}");
+                return None;
+            }
+        };
+        spinner.finish_and_clear();
+
+        let available: Vec<String> = entries
+            .iter()
+            .map(|entry| strip_prefix(ctx, &entry.key))
+            .collect();
+
+        let mut expanded = Vec::new();
+        for key in keys {
+            if !is_key_pattern(key) {
+                if !expanded.contains(key) {
+                    expanded.push(key.clone());
+                }
+                continue;
+            }
+
+            let matched: Vec<&String> = available
+                .iter()
+                .filter(|candidate| key_matches(key, candidate))
+                .collect();
+            if matched.is_empty() {
+                eprintln!("No keys matched '{}'.", key);
+            }
+            for candidate in matched {
+                if !expanded.contains(candidate) {
+                    expanded.push(candidate.clone());
+                }
+            }
+        }
+
+        if expanded.is_empty() {
+            None
+        } else {
+            Some(expanded)
+        }
+    }
+
+    fn print_key_list(heading: &str, keys: &[String]) {
+        println!("{heading}");
+        for key in keys {
+            println!("  {key}");
+        }
+    }
+
+    fn count_keys(count: usize) -> String {
+        if count == 1 {
+            "1 key".to_string()
+        } else {
+            format!("{count} keys")
+        }
+    }
+
+    /// Asks for a yes/no confirmation, treating a cancelled prompt as "no".
+    fn confirm_action(prompt: &str) -> bool {
+        match Confirm::new(prompt).with_default(false).prompt() {
+            Ok(answer) => answer,
+            Err(InquireError::OperationCanceled | InquireError::OperationInterrupted) => false,
+            Err(err) => {
+                eprintln!("Confirmation failed: {err}");
+                false
+            }
+        }
+    }
+
+    /// Confirms a destructive operation against `ctx`. `--yes` skips the yes/no question, but
+    /// protected labels always require typing the label name.
+    fn confirm_destructive(ctx: &ActiveKvContext, prompt: &str, yes: bool) -> bool {
+        if !yes && !confirm_action(prompt) {
+            return false;
+        }
+
+        let Some(label) = ctx.label.as_deref().filter(|_| ctx.is_protected()) else {
+            return true;
+        };
+        confirm_by_typing(&format!("Label '{label}' is protected"), label)
+    }
+
+    /// Asks for `name` to be typed back before a destructive operation; `reason` says why.
+    fn confirm_by_typing(reason: &str, name: &str) -> bool {
+        match Text::new(&format!("{reason}. Type its name to continue:")).prompt() {
+            Ok(input) if input.trim() == name => true,
+            Ok(_) => {
+                eprintln!("Name did not match.");
+                false
+            }
+            Err(InquireError::OperationCanceled | InquireError::OperationInterrupted) => false,
+            Err(err) => {
+                eprintln!("Confirmation failed: {err}");
+                false
+            }
+        }
+    }
+
+    /// Matches `key` against `pattern`, where `*` stands for any run of characters.
+    fn key_matches(pattern: &str, key: &str) -> bool {
+        let mut parts = pattern.split('*');
+        let first = parts.next().unwrap_or_default();
+        let Some(mut rest) = key.strip_prefix(first) else {
+            return false;
+        };
+
+        let parts: Vec<&str> = parts.collect();
+        let Some((last, middle)) = parts.split_last() else {
+            return rest.is_empty();
+        };
+
+        for part in middle {
+            match rest.find(part) {
+                Some(index) => rest = &rest[index + part.len()..],
+                None => return false,
             }
-            EntryValueType::KeyVault => match expected_secret_uri(ctx, full_key) {
-                Some(secret_uri) => {
-                    report.actions.push(PlanAction {
-                        key: entry.key.clone(),
-                        kind: PlanActionKind::CreateKeyVault { secret_uri },
-                    });
-                }
-                None => {}
-            },
-            EntryValueType::Prompt => {}
         }
-    }
 
-    fn expected_secret_uri(ctx: &ActiveKvContext, full_key: &str) -> Option<String> {
-        let vault_base = ensure_vault_base(ctx)?;
-        let secret_name = secret_name_from_key(full_key);
-        Some(format!("{}/secrets/{}", vault_base, secret_name))
+        rest.len() >= last.len() && rest.ends_with(last)
     }
 
-    fn display_secret_reference(secret_uri: &str) -> String {
-        parse_secret_uri(secret_uri)
-            .map(|(vault, name)| format!("{}/{}", vault, name))
-            .unwrap_or_else(|| secret_uri.to_string())
+    struct MoveAction {
+        from: String,
+        to: String,
+        entry: KeyValue,
+        /// New secret for the moved key when `--rename-secret` applies.
+        secret: Option<(KeyVaultReference, String)>,
     }
 
-    fn entry_type_name(value_type: EntryValueType) -> &'static str {
-        match value_type {
-            EntryValueType::Plain => "plain",
-            EntryValueType::KeyVault => "keyvault",
-            EntryValueType::Prompt => "prompt",
-        }
+    #[derive(Tabled)]
+    struct MoveRow {
+        #[tabled(rename = "FROM")]
+        from: String,
+        #[tabled(rename = "TO")]
+        to: String,
+        #[tabled(rename = "TYPE")]
+        value_type: String,
+        #[tabled(rename = "KEYVAULT")]
+        keyvault_ref: String,
     }
 
-    fn print_plan_report(ctx: &ActiveKvContext, report: &PlanReport, file: &Path) {
-        println!("Planning to update:");
-        println!(
-            "  app {}",
-            ctx.app_name
-                .as_deref()
-                .filter(|name| !name.is_empty())
-                .unwrap_or("none")
-        );
-        println!(
-            "  label {}",
-            ctx.label
-                .as_deref()
-                .filter(|label| !label.is_empty())
-                .unwrap_or("none")
-        );
-        println!("  config {}", ctx.config_name);
-        println!(
-            "  keyvault {}",
-            ctx.keyvault
-                .as_deref()
-                .filter(|kv| !kv.is_empty())
-                .unwrap_or("none")
-        );
+    /// Renames a key, or every key matching a `*` pattern, keeping its value, content type,
+    /// tags and Key Vault reference.
+    pub fn move_keys(
+        from: &str,
+        to: &str,
+        rename_secret: bool,
+        shared: SharedSecretPolicy,
+        dry_run: bool,
+        yes: bool,
+    ) {
+        let Some(ctx) = resolve_active_context(true, false) else {
+            return;
+        };
 
-        if report.actions.is_empty() {
-            println!(
-                "Configuration already matches snapshot '{}'.",
-                file.display()
+        let wildcards = (from.matches('*').count(), to.matches('*').count());
+        if wildcards != (0, 0) && wildcards != (1, 1) {
+            eprintln!(
+                "Use a single `*` in both names to move a subtree, e.g. `Db:*` `Database:*`."
             );
             return;
         }
 
-        let create_total = report
-            .actions
+        let spinner = create_spinner("Fetching configuration entries...");
+        let entries = match fetch_entries(&ctx) {
+            Ok(entries) => entries,
+            Err(err) => {
+                spinner.finish_and_clear();
+                eprintln!("Failed to fetch entries: {err}");
+                return;
+            }
+        };
+        spinner.finish_and_clear();
+
+        let existing: BTreeSet<String> = entries
             .iter()
-            .filter(|action| {
-                matches!(
-                    action.kind,
-                    PlanActionKind::CreatePlain | PlanActionKind::CreateKeyVault { .. }
-                )
-            })
-            .count();
-        let update_total = report
-            .actions
+            .map(|entry| strip_prefix(&ctx, &entry.key))
+            .collect();
+        let existing_secrets = existing_secret_references(&ctx, &entries);
+
+        let mut actions = Vec::new();
+        for entry in entries {
+            let key = strip_prefix(&ctx, &entry.key);
+            let Some(target) = move_target(from, to, &key) else {
+                continue;
+            };
+            if target == key {
+                continue;
+            }
+
+            let secret = match keyvault_reference_from_entry(&entry) {
+                Some(reference) if rename_secret && reference.is_secret() => {
+                    let Some(uri) = generated_secret_uri(&ctx, &prefix_key(&ctx, &target)) else {
+                        return;
+                    };
+                    (secret_identity(&uri) != reference.identity()).then_some((reference, uri))
+                }
+                _ => None,
+            };
+
+            actions.push(MoveAction {
+                from: key,
+                to: target,
+                entry,
+                secret,
+            });
+        }
+
+        if actions.is_empty() {
+            println!("No keys matched '{}'.", from);
+            return;
+        }
+
+        let conflicts: Vec<&str> = actions
             .iter()
-            .filter(|action| {
-                matches!(
-                    action.kind,
-                    PlanActionKind::UpdatePlain | PlanActionKind::UpdateKeyVault { .. }
-                )
-            })
-            .count();
+            .map(|action| action.to.as_str())
+            .filter(|target| existing.contains(*target))
+            .collect();
+        if !conflicts.is_empty() {
+            eprintln!(
+                "Refusing to overwrite existing keys: {}",
+                conflicts.join(", ")
+            );
+            return;
+        }
 
-        let rows: Vec<PlanTableRow> = report
-            .actions
+        let planned_secrets: Vec<(String, String)> = actions
             .iter()
-            .map(|action| match &action.kind {
-                PlanActionKind::CreatePlain => PlanTableRow {
-                    status: "+".to_string(),
-                    value_type: "plain".to_string(),
-                    name: action.key.clone(),
-                    plan: "create".to_string(),
-                    keyvault_ref: String::new(),
-                },
-                PlanActionKind::UpdatePlain => PlanTableRow {
-                    status: "~".to_string(),
-                    value_type: "plain".to_string(),
-                    name: action.key.clone(),
-                    plan: "update".to_string(),
-                    keyvault_ref: String::new(),
-                },
-                PlanActionKind::CreateKeyVault { secret_uri } => PlanTableRow {
-                    status: "+".to_string(),
-                    value_type: "keyvault".to_string(),
-                    name: action.key.clone(),
-                    plan: "create".to_string(),
-                    keyvault_ref: display_secret_reference(secret_uri),
-                },
-                PlanActionKind::UpdateKeyVault { secret_uri } => PlanTableRow {
-                    status: "~".to_string(),
-                    value_type: "keyvault".to_string(),
-                    name: action.key.clone(),
-                    plan: "update".to_string(),
-                    keyvault_ref: display_secret_reference(secret_uri),
-                },
-                PlanActionKind::TypeChange {
-                    from_type,
-                    to_type,
-                    detail,
-                } => {
-                    let transition = format!(
-                        "{} -> {}",
-                        entry_type_name(*from_type),
-                        entry_type_name(*to_type)
+            .filter_map(|action| {
+                let (_, uri) = action.secret.as_ref()?;
+                Some((action.to.clone(), uri.clone()))
+            })
+            .collect();
+        let collisions = secret_collisions(existing_secrets, &planned_secrets);
+        if !collisions.is_empty() {
+            print_secret_collisions(&collisions);
+            eprintln!("Refusing to rename secrets onto secrets other keys use.");
+            return;
+        }
+
+        print_move_plan(&actions);
+        if dry_run {
+            return;
+        }
+
+        if !confirm_destructive(&ctx, &format!("Move {}?", count_keys(actions.len())), yes) {
+            println!("No keys moved.");
+            return;
+        }
+
+        let Some(actions) = resolve_move_secret_targets(&ctx, actions, shared) else {
+            return;
+        };
+        if actions.is_empty() {
+            println!("No keys moved.");
+            return;
+        }
+
+        let mut moved = 0usize;
+        let mut renamed_secrets = 0usize;
+        for action in &actions {
+            let spinner = create_spinner(&format!("Moving '{}'...", action.from));
+            let result = spinner.suspend(|| move_entry(&ctx, action));
+            spinner.finish_and_clear();
+            if result {
+                moved += 1;
+                renamed_secrets += usize::from(action.secret.is_some());
+                println!("Moved '{}' → '{}'.", action.from, action.to);
+            }
+        }
+
+        if moved > 1 {
+            println!("Moved {} of {} keys.", moved, actions.len());
+        }
+        if renamed_secrets > 0 {
+            println!(
+                "The previous secrets were kept; run `azac keyvault gc` to remove them once unused."
+            );
+        }
+    }
+
+    /// Applies the shared-secret policy to every secret a rename would write. Redirects scoped
+    /// secrets and drops refused moves.
+    fn resolve_move_secret_targets(
+        ctx: &ActiveKvContext,
+        actions: Vec<MoveAction>,
+        policy: SharedSecretPolicy,
+    ) -> Option<Vec<MoveAction>> {
+        if actions.iter().all(|action| action.secret.is_none()) {
+            return Some(actions);
+        }
+
+        let usage = load_secret_usage(ctx)?;
+        let mut kept = Vec::with_capacity(actions.len());
+        for mut action in actions {
+            if let Some((_, uri)) = &mut action.secret {
+                let full_key = prefix_key(ctx, &action.to);
+                match resolve_secret_target(ctx, &usage, &full_key, uri, policy) {
+                    Some(target) => *uri = target,
+                    None => continue,
+                }
+            }
+            kept.push(action);
+        }
+        Some(kept)
+    }
+
+    /// The key `key` moves to, or `None` when it is not selected by `from`.
+    fn move_target(from: &str, to: &str, key: &str) -> Option<String> {
+        let Some((prefix, suffix)) = from.split_once('*') else {
+            return (key == from).then(|| to.to_string());
+        };
+        let rest = key.strip_prefix(prefix)?;
+        let matched = rest.strip_suffix(suffix)?;
+        Some(to.replacen('*', matched, 1))
+    }
+
+    fn move_entry(ctx: &ActiveKvContext, action: &MoveAction) -> bool {
+        let mut value = action.entry.value.clone().unwrap_or_default();
+
+        if let Some((reference, uri)) = &action.secret {
+            let secret_value = match fetch_secret_value(&reference.uri()) {
+                Ok(value) => value,
+                Err(err) => {
+                    eprintln!("Failed to read secret '{}': {}", reference, err);
+                    return false;
+                }
+            };
+            let version_id = match set_secret_value(uri, &secret_value) {
+                Ok(id) => id,
+                Err(err) => {
+                    eprintln!(
+                        "Failed to create secret '{}': {}",
+                        display_secret_reference(uri),
+                        err
                     );
-                    PlanTableRow {
-                        status: "!".to_string(),
-                        value_type: transition,
-                        name: action.key.clone(),
-                        plan: "type change".to_string(),
-                        keyvault_ref: detail.clone().unwrap_or_default(),
+                    return false;
+                }
+            };
+            // Keep the reference pinned when the original was.
+            let target = if reference.version.is_some() {
+                version_id
+            } else {
+                uri.clone()
+            };
+            let Some(target) = KeyVaultReference::from_uri(&target) else {
+                eprintln!("Unexpected secret identifier '{}'.", target);
+                return false;
+            };
+            value = target.to_json();
+        }
+
+        let to_key = prefix_key(ctx, &action.to);
+        if let Err(err) = write_entry_with_tags(
+            ctx,
+            &to_key,
+            &value,
+            action.entry.content_type.as_deref(),
+            action.entry.tags.as_ref(),
+        ) {
+            eprintln!("Failed to write '{}': {}", action.to, err);
+            return false;
+        }
+
+        if let Err(err) = delete_entry(ctx, &action.entry.key) {
+            eprintln!(
+                "Wrote '{}' but failed to delete '{}': {}",
+                action.to, action.from, err
+            );
+            return false;
+        }
+
+        true
+    }
+
+    fn print_move_plan(actions: &[MoveAction]) {
+        let rows: Vec<MoveRow> = actions
+            .iter()
+            .map(|action| {
+                let reference = keyvault_reference_from_entry(&action.entry);
+                let keyvault_ref = match (&action.secret, &reference) {
+                    (Some((from, to)), _) => {
+                        format!("{} -> {}", from, display_secret_reference(to))
                     }
+                    (None, Some(reference)) => reference.to_string(),
+                    (None, None) => String::new(),
+                };
+                MoveRow {
+                    from: action.from.clone(),
+                    to: action.to.clone(),
+                    value_type: if reference.is_some() {
+                        "keyvault".to_string()
+                    } else {
+                        "plain".to_string()
+                    },
+                    keyvault_ref,
                 }
             })
             .collect();
 
         let mut table = Table::new(rows);
         table.with(Style::blank());
-
-        let table_str = table.to_string();
+        println!("{table}");
         println!();
-        for line in table_str.lines() {
-            println!("{}", line);
-        }
+    }
 
-        let type_change_total = report
-            .actions
-            .iter()
-            .filter(|action| matches!(action.kind, PlanActionKind::TypeChange { .. }))
-            .count();
+    /// Environments compared by `azac diff`. Exactly one list is expected.
+    pub struct DiffOptions {
+        pub labels: Vec<String>,
+        pub apps: Vec<String>,
+        pub contexts: Vec<String>,
+        pub resolve_secrets: bool,
+        pub full: bool,
+    }
 
-        println!();
-        println!("Summary:");
-        println!("  + {} to create", create_total);
-        println!("  ~ {} to update", update_total);
-        println!("  ! {} type change", type_change_total);
+    #[derive(Default)]
+    struct KeyDiff {
+        missing: bool,
+        type_mismatch: bool,
+        differs: bool,
+    }
+
+    impl KeyDiff {
+        fn is_identical(&self) -> bool {
+            !(self.missing || self.type_mismatch || self.differs)
+        }
+
+        fn label(&self) -> String {
+            let mut parts = Vec::new();
+            if self.missing {
+                parts.push("missing");
+            }
+            if self.type_mismatch {
+                parts.push("type mismatch");
+            }
+            if self.differs {
+                parts.push("differs");
+            }
+            if parts.is_empty() {
+                "identical".to_string()
+            } else {
+                parts.join(", ")
+            }
+        }
     }
 
-    pub fn import_entries(path: &Path) {
-        let Some(ctx) = resolve_active_context(true, false) else {
+    /// Compares the app's entries across labels, apps or saved contexts.
+    pub fn diff_environments(options: &DiffOptions) {
+        let Some(ctx) = resolve_active_context(false, false) else {
             return;
         };
-
-        let Some(entries) = parse_import_map(path) else {
+        let Some(sides) = resolve_diff_sides(&ctx, options) else {
             return;
         };
+        if sides.len() < 2 {
+            eprintln!("Name at least two environments to compare.");
+            return;
+        }
 
-        let mut prepared_entries = Vec::new();
-        let mut skipped = 0usize;
+        let spinner = create_spinner("Fetching configuration entries...");
+        let results: Vec<AzCliResult<BTreeMap<String, EntrySnapshot>>> = thread::scope(|scope| {
+            let handles: Vec<_> = sides
+                .iter()
+                .map(|(_, side)| {
+                    scope.spawn(|| collect_export_snapshots(side, options.resolve_secrets))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("diff worker panicked"))
+                .collect()
+        });
+        spinner.finish_and_clear();
 
-        for mut entry in entries {
-            if entry.value_type == EntryValueType::Prompt {
-                match prompt_value_type(&entry.key) {
-                    Some(kind) => entry.value_type = kind,
-                    None => {
-                        println!("Skipping '{}' as requested.", entry.key);
-                        skipped += 1;
-                        continue;
-                    }
+        let mut snapshots = Vec::with_capacity(sides.len());
+        for ((name, _), result) in sides.iter().zip(results) {
+            match result {
+                Ok(entries) => snapshots.push(entries),
+                Err(err) => {
+                    eprintln!("Failed to fetch entries for '{}': {err}", name);
+                    return;
                 }
             }
-
-            prepared_entries.push(entry);
         }
 
-        if prepared_entries.is_empty() {
-            if skipped > 0 {
-                println!(
-                    "No entries to import; skipped {} {} during prompting.",
-                    skipped,
-                    if skipped == 1 { "entry" } else { "entries" }
-                );
-            } else {
-                println!("No entries to import.");
-            }
+        let keys: BTreeSet<&String> = snapshots.iter().flat_map(|side| side.keys()).collect();
+        let diffs: Vec<(&String, KeyDiff)> = keys
+            .into_iter()
+            .map(|key| {
+                let present: Vec<&EntrySnapshot> =
+                    snapshots.iter().filter_map(|side| side.get(key)).collect();
+                let first = present[0];
+                let diff = KeyDiff {
+                    missing: present.len() < snapshots.len(),
+                    type_mismatch: present
+                        .iter()
+                        .any(|entry| entry.value_type != first.value_type),
+                    // Compare every pair so sides of the same type are checked even when
+                    // another side has a different type.
+                    differs: present.iter().enumerate().any(|(index, entry)| {
+                        present[index + 1..].iter().any(|other| {
+                            entry.value_type == other.value_type && entry.value != other.value
+                        })
+                    }),
+                };
+                (key, diff)
+            })
+            .collect();
+
+        let names: Vec<&str> = sides.iter().map(|(name, _)| name.as_str()).collect();
+        print_diff_summary(&names, &snapshots, &diffs);
+
+        let rows: Vec<&(&String, KeyDiff)> = diffs
+            .iter()
+            .filter(|(_, diff)| options.full || !diff.is_identical())
+            .collect();
+        if rows.is_empty() {
             return;
         }
 
-        let total = prepared_entries.len();
-        let config_name = ctx.config_name.clone();
-        let ctx = Arc::new(ctx);
-        let queue = Arc::new(Mutex::new(VecDeque::from(prepared_entries)));
-        let successes = Arc::new(AtomicUsize::new(0));
-        let failures = Arc::new(AtomicUsize::new(0));
-
-        let multi = MultiProgress::new();
-        let summary = multi.add(ProgressBar::new(total as u64));
-        if let Ok(style) = ProgressStyle::with_template(
-            "{spinner:.green} [{elapsed_precise}] {wide_bar:.cyan/blue} {pos}/{len} {msg}",
-        ) {
-            summary.set_style(style);
+        let mut builder = Builder::default();
+        let mut header = vec!["KEY".to_string(), "STATUS".to_string()];
+        header.extend(names.iter().map(|name| name.to_string()));
+        builder.push_record(header);
+        for (key, diff) in rows {
+            let mut record = vec![key.to_string(), diff.label()];
+            for side in &snapshots {
+                record.push(match side.get(*key) {
+                    None => "—".to_string(),
+                    Some(entry) if options.full => diff_cell_value(entry),
+                    Some(entry) => entry_type_name(entry.value_type).to_string(),
+                });
+            }
+            builder.push_record(record);
         }
-        summary.set_message("Import summary");
-        summary.enable_steady_tick(Duration::from_millis(80));
 
-        let entry_style = ProgressStyle::with_template("{spinner:.cyan} {msg}")
-            .unwrap_or_else(|_| ProgressStyle::default_spinner());
-        let entry_style = Arc::new(entry_style);
+        let mut table = builder.build();
+        table
+            .with(Style::psql())
+            .with(BorderColor::filled(Color::FG_BRIGHT_BLACK))
+            .modify(Rows::first(), Color::BOLD | Color::FG_BRIGHT_WHITE);
+        println!();
+        println!("{table}");
+    }
 
-        let mut worker_count = thread::available_parallelism()
-            .map(|value| value.get())
-            .unwrap_or(4);
-        worker_count = worker_count.min(total).max(1);
+    /// Builds one context per compared environment, named after what varies between them.
+    fn resolve_diff_sides(
+        ctx: &ActiveKvContext,
+        options: &DiffOptions,
+    ) -> Option<Vec<(String, ActiveKvContext)>> {
+        if !options.contexts.is_empty() {
+            let (_, context) = super::load_context()?;
+            let mut sides = Vec::new();
+            for name in &options.contexts {
+                let Some(saved) = context.saved.get(name) else {
+                    eprintln!("No saved context named '{name}'.");
+                    return None;
+                };
+                sides.push((name.clone(), kv_context_from(saved)));
+            }
+            return Some(sides);
+        }
 
-        let mut handles = Vec::with_capacity(worker_count);
-        for _ in 0..worker_count {
-            let ctx = Arc::clone(&ctx);
-            let queue = Arc::clone(&queue);
-            let success_counter = Arc::clone(&successes);
-            let failure_counter = Arc::clone(&failures);
-            let summary_bar = summary.clone();
-            let mp = multi.clone();
-            let entry_style = Arc::clone(&entry_style);
+        if !options.apps.is_empty() {
+            return Some(
+                options
+                    .apps
+                    .iter()
+                    .map(|app| {
+                        let side = ActiveKvContext {
+                            app_name: Some(app.clone()),
+                            ..ctx.clone()
+                        };
+                        (app.clone(), side)
+                    })
+                    .collect(),
+            );
+        }
 
-            handles.push(thread::spawn(move || {
-                loop {
-                    let entry = {
-                        let mut guard = queue.lock().expect("import queue poisoned");
-                        guard.pop_front()
-                    };
+        if ctx.app_name.is_none() {
+            eprintln!("No application selected. Run `azac setup` to pick one.");
+            return None;
+        }
 
-                    let Some(entry) = entry else {
-                        break;
+        Some(
+            options
+                .labels
+                .iter()
+                .map(|label| {
+                    let side = ActiveKvContext {
+                        label: Some(label.clone()).filter(|label| !label.is_empty()),
+                        ..ctx.clone()
                     };
-
-                    let spinner = mp.add(ProgressBar::new_spinner());
-                    spinner.set_style(entry_style.as_ref().clone());
-                    spinner.set_message(format!("{} {}", entry.key, entry.value_type.label()));
-                    spinner.enable_steady_tick(Duration::from_millis(80));
-
-                    if process_import_entry(ctx.as_ref(), &entry) {
-                        success_counter.fetch_add(1, Ordering::Relaxed);
-                        spinner.finish_with_message(format!(
-                            "✔ {} {}",
-                            entry.key,
-                            entry.value_type.label()
-                        ));
+                    let name = if label.is_empty() {
+                        "(none)".to_string()
                     } else {
-                        failure_counter.fetch_add(1, Ordering::Relaxed);
-                        spinner.finish_with_message(format!("✖ {} (see logs)", entry.key));
-                    }
-
-                    summary_bar.inc(1);
-                }
-            }));
-        }
+                        label.clone()
+                    };
+                    (name, side)
+                })
+                .collect(),
+        )
+    }
 
-        for handle in handles {
-            if let Err(err) = handle.join() {
-                eprintln!("An import worker thread panicked: {err:?}");
+    fn diff_cell_value(entry: &EntrySnapshot) -> String {
+        match &entry.secret_uri {
+            Some(uri) if !entry.value_known => {
+                format!("kv:{}", truncate_value(&display_secret_reference(uri), 40))
             }
+            Some(_) => format!("kv:{}", truncate_value(&entry.value, 40)),
+            None if entry.value.is_empty() => "(empty)".to_string(),
+            None => truncate_value(&entry.value, 40),
         }
+    }
 
-        summary.finish_with_message(format!(
-            "Imported {} of {} entries into '{}'.",
-            successes.load(Ordering::Relaxed),
-            total,
-            config_name
-        ));
-        if skipped > 0 {
-            println!(
-                "Skipped {} {} during prompting.",
-                skipped,
-                if skipped == 1 { "entry" } else { "entries" }
-            );
-        }
+    fn print_diff_summary(
+        names: &[&str],
+        snapshots: &[BTreeMap<String, EntrySnapshot>],
+        diffs: &[(&String, KeyDiff)],
+    ) {
+        let count =
+            |check: fn(&KeyDiff) -> bool| diffs.iter().filter(|(_, diff)| check(diff)).count();
 
-        let failed = failures.load(Ordering::Relaxed);
-        if failed > 0 {
-            eprintln!(
-                "Failed to import {} {}. See logs above for details.",
-                failed,
-                if failed == 1 { "entry" } else { "entries" }
-            );
+        println!("Comparing {}", names.join(", "));
+        println!(
+            "  {} keys, {} identical",
+            diffs.len(),
+            count(KeyDiff::is_identical)
+        );
+        println!("  ~ {} with differing values", count(|diff| diff.differs));
+        println!(
+            "  ! {} with type mismatches",
+            count(|diff| diff.type_mismatch)
+        );
+        println!("  - {} missing somewhere", count(|diff| diff.missing));
+        for (name, side) in names.iter().zip(snapshots) {
+            let missing = diffs
+                .iter()
+                .filter(|(key, _)| !side.contains_key(*key))
+                .count();
+            if missing > 0 {
+                println!("      {} missing in {}", missing, name);
+            }
         }
     }
 
     fn resolve_active_context(require_app: bool, require_label: bool) -> Option<ActiveKvContext> {
-        let (_, context) = match super::load_context() {
-            Some(value) => value,
-            None => return None,
-        };
+        let (_, context) = super::load_context()?;
 
         let Some(active) = context.active.as_ref() else {
             super::missing_setup_message();
             return None;
         };
 
-        let app_name = active.app.name.clone();
-
-        if require_app && app_name.is_none() {
+        if require_app && active.app.name.is_none() {
             eprintln!("No application selected. Run `azac setup` to pick one.");
             return None;
         }
 
-        let label = active.app.label.clone().filter(|lbl| !lbl.is_empty());
-        if require_label && label.is_none() {
+        if require_label && active.app.label.as_deref().is_none_or(str::is_empty) {
             eprintln!("No label configured for the current application.");
             return None;
         }
 
-        let keyvault = active.app.keyvault.clone().filter(|kv| !kv.is_empty());
+        Some(kv_context_from(active))
+    }
 
-        Some(ActiveKvContext {
+    fn kv_context_from(active: &ActiveContext) -> ActiveKvContext {
+        ActiveKvContext {
             config_name: active.config_name.clone(),
             endpoint: super::normalize_appconfig_endpoint(&active.config_name, &active.endpoint),
             separator: active.separator.clone(),
-            app_name,
-            label,
-            keyvault,
-        })
+            app_name: active.app.name.clone(),
+            label: active.app.label.clone().filter(|lbl| !lbl.is_empty()),
+            keyvault: active.app.keyvault.clone().filter(|kv| !kv.is_empty()),
+            secret_naming: active.secret_naming.clone(),
+            protected_labels: active.protected_labels.clone(),
+        }
+    }
+
+    /// Fetches the raw entries for the active app and label, reporting failures.
+    pub fn fetch_active_entries() -> Option<Vec<KeyValue>> {
+        let ctx = resolve_active_context(true, false)?;
+
+        let spinner = create_spinner("Fetching configuration entries...");
+        let result = fetch_entries(&ctx);
+        spinner.finish_and_clear();
+
+        match result {
+            Ok(entries) => Some(entries),
+            Err(err) => {
+                eprintln!("Failed to fetch entries: {err}");
+                None
+            }
+        }
     }
 
     fn fetch_entries(ctx: &ActiveKvContext) -> AzCliResult<Vec<KeyValue>> {
@@ -1689,6 +4468,22 @@ pub mod kv {
         az(args)
     }
 
+    /// Lists every key under every label, regardless of the active app.
+    fn fetch_all_entries(ctx: &ActiveKvContext) -> AzCliResult<Vec<KeyValue>> {
+        az([
+            "appconfig",
+            "kv",
+            "list",
+            "--endpoint",
+            &ctx.endpoint,
+            "--auth-mode",
+            "login",
+            "--all",
+            "-o",
+            "json",
+        ])
+    }
+
     fn show_entry(ctx: &ActiveKvContext, full_key: &str) -> AzCliResult<KeyValue> {
         let mut args = vec![
             "appconfig".to_string(),
@@ -1717,6 +4512,16 @@ pub mod kv {
         full_key: &str,
         value: &str,
         content_type: Option<&str>,
+    ) -> AzCliResult<KeyValue> {
+        write_entry_with_tags(ctx, full_key, value, content_type, None)
+    }
+
+    fn write_entry_with_tags(
+        ctx: &ActiveKvContext,
+        full_key: &str,
+        value: &str,
+        content_type: Option<&str>,
+        tags: Option<&BTreeMap<String, String>>,
     ) -> AzCliResult<KeyValue> {
         let mut args = vec![
             "appconfig".to_string(),
@@ -1740,6 +4545,11 @@ pub mod kv {
             args.push(ct.to_string());
         }
 
+        if let Some(tags) = tags.filter(|tags| !tags.is_empty()) {
+            args.push("--tags".to_string());
+            args.extend(tags.iter().map(|(name, value)| format!("{name}={value}")));
+        }
+
         if let Some(label) = &ctx.label {
             args.push("--label".to_string());
             args.push(label.clone());
@@ -1752,6 +4562,15 @@ pub mod kv {
         ctx: &ActiveKvContext,
         full_key: &str,
         secret_uri: &str,
+    ) -> AzCliResult<KeyValue> {
+        write_keyvault_entry_with_tags(ctx, full_key, secret_uri, None)
+    }
+
+    fn write_keyvault_entry_with_tags(
+        ctx: &ActiveKvContext,
+        full_key: &str,
+        secret_uri: &str,
+        tags: Option<&BTreeMap<String, String>>,
     ) -> AzCliResult<KeyValue> {
         let mut args = vec![
             "appconfig".to_string(),
@@ -1770,6 +4589,11 @@ pub mod kv {
             "json".to_string(),
         ];
 
+        if let Some(tags) = tags.filter(|tags| !tags.is_empty()) {
+            args.push("--tags".to_string());
+            args.extend(tags.iter().map(|(name, value)| format!("{name}={value}")));
+        }
+
         if let Some(label) = &ctx.label {
             args.push("--label".to_string());
             args.push(label.clone());
@@ -1805,7 +4629,8 @@ pub mod kv {
 
     fn resolve_value(entry: &KeyValue, fetch_secret: bool, show_activity: bool) -> (String, bool) {
         if let Some(uri) = keyvault_uri_from_entry(entry) {
-            if fetch_secret {
+            // Certificates and keys have no value to read; callers report them as unresolved.
+            if fetch_secret && reference_kind(&uri) == ReferenceKind::Secret {
                 let spinner = show_activity.then(|| create_spinner("Fetching Key Vault secret..."));
 
                 let result = fetch_secret_value(&uri);
@@ -1836,85 +4661,120 @@ pub mod kv {
     }
 
     fn keyvault_uri_from_entry(entry: &KeyValue) -> Option<String> {
-        if let Some(value) = entry.value.as_deref() {
-            if let Some(uri) = parse_keyvault_reference(value) {
-                return Some(uri);
-            }
-            if let Some(uri) = parse_keyvault_json(value) {
-                return Some(uri);
-            }
-        }
+        keyvault_reference_from_entry(entry).map(|reference| reference.uri())
+    }
 
-        if entry
-            .content_type
-            .as_deref()
-            .map(|ct| ct.contains("keyvaultref"))
-            .unwrap_or(false)
-        {
-            if let Some(value) = entry.value.as_deref() {
-                if let Some(uri) = parse_keyvault_json(value) {
-                    return Some(uri);
-                }
-            }
-        }
+    fn keyvault_reference_from_entry(entry: &KeyValue) -> Option<KeyVaultReference> {
+        let value = entry.value.as_deref()?;
+        KeyVaultReference::from_app_service(value).or_else(|| KeyVaultReference::from_json(value))
+    }
 
-        None
+    fn fetch_secret_value(uri: &str) -> AzCliResult<String> {
+        Ok(fetch_secret(uri)?.value)
     }
 
-    fn parse_keyvault_reference(value: &str) -> Option<String> {
-        const PREFIX: &str = "@Microsoft.KeyVault(SecretUri=";
-        const SUFFIX: &str = ")";
+    fn fetch_secret(uri: &str) -> AzCliResult<SecretValue> {
+        az(["keyvault", "secret", "show", "--id", uri, "-o", "json"])
+    }
 
-        if value.starts_with(PREFIX) && value.ends_with(SUFFIX) {
-            let inner = &value[PREFIX.len()..value.len() - SUFFIX.len()];
-            if inner.is_empty() {
-                None
-            } else {
-                Some(inner.to_string())
-            }
-        } else {
-            None
-        }
+    /// Reads the attributes of the secret, certificate or key behind `uri` without its value.
+    fn fetch_object_details(uri: &str) -> AzCliResult<ObjectDetails> {
+        let kind = reference_kind(uri);
+        az(["keyvault", kind.label(), "show", "--id", uri, "-o", "json"])
     }
 
-    fn parse_keyvault_json(value: &str) -> Option<String> {
-        let json: serde_json::Value = serde_json::from_str(value).ok()?;
-        json.get("uri")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
-            .or_else(|| {
-                json.get("secretUri")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-            })
+    fn reference_kind(uri: &str) -> ReferenceKind {
+        KeyVaultReference::parse(uri)
+            .map(|reference| reference.kind)
+            .unwrap_or_default()
     }
 
-    fn fetch_secret_value(uri: &str) -> AzCliResult<String> {
-        let secret: SecretValue = az(["keyvault", "secret", "show", "--id", uri, "-o", "json"])?;
-        Ok(secret.value)
+    /// Writes a new version of the secret behind `uri` and returns the versioned secret id.
+    fn set_secret_value(uri: &str, value: &str) -> AzCliResult<String> {
+        let (vault_name, secret_name) =
+            parse_secret_uri(uri).ok_or_else(|| AzCliError::CommandFailure {
+                code: None,
+                stderr: match reference_kind(uri) {
+                    ReferenceKind::Secret => format!("Invalid Key Vault secret URI: {uri}"),
+                    kind => format!(
+                        "Key Vault {} references cannot be written: {uri}",
+                        kind.label()
+                    ),
+                },
+            })?;
+
+        // `secret set` replaces the tags, so carry the current ones over. Only secrets azac
+        // creates get the azac tag; the grace-period marker stays on the version it was set on.
+        let current: AzCliResult<SecretVersion> = az([
+            "keyvault",
+            "secret",
+            "show",
+            "--vault-name",
+            &vault_name,
+            "--name",
+            &secret_name,
+            "-o",
+            "json",
+        ]);
+        let mut tags = match current {
+            Ok(secret) => secret.tags.unwrap_or_default(),
+            Err(err) if err.is_not_found() => {
+                BTreeMap::from([(AZAC_TAG_NAME.to_string(), AZAC_TAG_VALUE.to_string())])
+            }
+            Err(err) => return Err(err),
+        };
+        tags.remove(AZAC_RETIRE_TAG);
+
+        let mut args = vec![
+            "keyvault".to_string(),
+            "secret".to_string(),
+            "set".to_string(),
+            "--vault-name".to_string(),
+            vault_name,
+            "--name".to_string(),
+            secret_name,
+            "--value".to_string(),
+            value.to_string(),
+            "-o".to_string(),
+            "json".to_string(),
+        ];
+        if !tags.is_empty() {
+            args.push("--tags".to_string());
+            args.extend(tags.iter().map(|(name, value)| format!("{name}={value}")));
+        }
+
+        let secret: SecretVersion = az(args)?;
+        Ok(secret.id)
     }
 
-    fn set_secret_value(uri: &str, value: &str) -> AzCliResult<()> {
-        let (vault_name, secret_name) =
-            parse_secret_uri(uri).ok_or_else(|| AzCliError::CommandFailure {
-                code: None,
-                stderr: format!("Invalid Key Vault secret URI: {uri}"),
-            })?;
+    /// Writes `value` as a new version of the secret behind `secret_uri`. A pinned reference is
+    /// moved to the new version; returns the reference now in effect. When the new version is
+    /// written but the key cannot be re-pinned, reports the new version so it can be pinned by
+    /// hand.
+    fn roll_secret(
+        ctx: &ActiveKvContext,
+        full_key: &str,
+        secret_uri: &str,
+        value: &str,
+    ) -> AzCliResult<String> {
+        let new_id = set_secret_value(secret_uri, value)?;
+        if parse_secret_version(secret_uri).is_none() {
+            return Ok(secret_uri.to_string());
+        }
 
-        let _: serde_json::Value = az([
-            "keyvault",
-            "secret",
-            "set",
-            "--vault-name",
-            &vault_name,
-            "--name",
-            &secret_name,
-            "--value",
-            value,
-            "-o",
-            "json",
-        ])?;
-        Ok(())
+        if let Err(err) = write_keyvault_entry(ctx, full_key, &new_id) {
+            let key = strip_prefix(ctx, full_key);
+            let version = parse_secret_version(&new_id).unwrap_or_default();
+            eprintln!(
+                "Wrote secret version '{}', but key '{}' still points at '{}'.",
+                display_secret_reference(&new_id),
+                key,
+                display_secret_reference(secret_uri)
+            );
+            eprintln!("Run `azac secret pin {key} {version}` to move it to the new version.");
+            return Err(err);
+        }
+        Ok(new_id)
     }
 
     fn prefix_key(ctx: &ActiveKvContext, key: &str) -> String {
@@ -1934,96 +4794,206 @@ pub mod kv {
         }
     }
 
-    #[derive(Debug)]
-    struct ImportEntry {
-        key: String,
-        value: String,
-        value_type: EntryValueType,
+    /// Reads import entries from an azac YAML file, or from any convert source with `--from`.
+    fn load_import_source(
+        ctx: &ActiveKvContext,
+        path: &Path,
+        source: &SourceOptions,
+    ) -> Option<Vec<ImportEntry>> {
+        let Some(format) = source.from else {
+            return entries::parse_import_map(path);
+        };
+
+        let vars =
+            match convert::read_source(format, path, &ctx.separator, source.service.as_deref()) {
+                Ok(vars) => vars,
+                Err(err) => {
+                    eprintln!("{err}");
+                    return None;
+                }
+            };
+
+        if vars.is_empty() {
+            eprintln!("No entries found in {}.", path.display());
+            return None;
+        }
+
+        Some(convert::to_import_entries(&vars, source.detect_secrets))
     }
 
-    fn parse_import_map(path: &Path) -> Option<Vec<ImportEntry>> {
-        let contents = match fs::read_to_string(path) {
-            Ok(data) => data,
+    /// Keys whose live value the import would replace.
+    fn overwritten_keys(ctx: &ActiveKvContext, entries: &[ImportEntry]) -> Option<Vec<String>> {
+        let spinner = create_spinner("Fetching configuration entries...");
+        let result = collect_export_snapshots(ctx, false);
+        spinner.finish_and_clear();
+        let live = match result {
+            Ok(live) => live,
             Err(err) => {
-                eprintln!("Failed to read {}: {err}", path.display());
+                eprintln!("Failed to fetch entries: {err}");
                 return None;
             }
         };
 
-        if contents.trim().is_empty() {
-            eprintln!("Import file {} is empty.", path.display());
-            return None;
+        Some(
+            entries
+                .iter()
+                .filter(|entry| {
+                    let Some(snapshot) = live.get(&entry.key) else {
+                        return false;
+                    };
+                    let unchanged = match (&entry.reference, &snapshot.secret_uri) {
+                        (Some(reference), Some(current)) => reference == current,
+                        (None, None) => {
+                            entry.value_type == EntryValueType::Plain
+                                && entry.value == snapshot.value
+                        }
+                        _ => false,
+                    };
+                    !unchanged
+                })
+                .map(|entry| entry.key.clone())
+                .collect(),
+        )
+    }
+
+    /// Verifies that secrets generated for new Key Vault entries don't collide with each other
+    /// or with secrets already referenced by the app.
+    fn check_import_collisions(ctx: &ActiveKvContext, entries: &[ImportEntry]) -> bool {
+        let generated: Vec<&ImportEntry> = entries
+            .iter()
+            .filter(|entry| {
+                entry.value_type == EntryValueType::KeyVault && entry.reference.is_none()
+            })
+            .collect();
+        if generated.is_empty() || ensure_vault_base(ctx).is_none() {
+            return true;
         }
 
-        match parse_yaml_entries(&contents) {
-            Ok(entries) if entries.is_empty() => {
-                eprintln!("No entries found in {}.", path.display());
-                None
-            }
-            Ok(entries) => Some(entries),
+        let live = match fetch_entries(ctx) {
+            Ok(entries) => entries,
             Err(err) => {
-                eprintln!("Failed to parse {} as YAML: {}", path.display(), err);
-                None
+                eprintln!("Failed to check existing Key Vault references: {err}");
+                return false;
             }
-        }
-    }
+        };
+        let live_keys: BTreeSet<String> = live
+            .iter()
+            .map(|entry| strip_prefix(ctx, &entry.key))
+            .collect();
 
-    fn parse_yaml_entries(contents: &str) -> Result<Vec<ImportEntry>, String> {
-        let value: serde_json::Value =
-            serde_yaml::from_str(contents).map_err(|err| err.to_string())?;
-        entries_from_json_value(value)
-    }
+        let planned: Vec<(String, String)> = generated
+            .into_iter()
+            .filter(|entry| !live_keys.contains(&entry.key))
+            .filter_map(|entry| {
+                let full_key = prefix_key(ctx, &entry.key);
+                expected_secret_uri(ctx, &full_key).map(|uri| (entry.key.clone(), uri))
+            })
+            .collect();
 
-    fn entries_from_json_value(value: serde_json::Value) -> Result<Vec<ImportEntry>, String> {
-        let map = value
-            .as_object()
-            .ok_or_else(|| "Import file must contain a mapping of keys to values.".to_string())?;
+        let collisions = secret_collisions(existing_secret_references(ctx, &live), &planned);
+        if collisions.is_empty() {
+            return true;
+        }
 
-        Ok(map_to_entries(map))
+        print_secret_collisions(&collisions);
+        eprintln!(
+            "Import aborted. Adjust the naming template with `azac keyvault naming` to keep secrets apart."
+        );
+        false
     }
 
-    fn map_to_entries(map: &serde_json::Map<String, serde_json::Value>) -> Vec<ImportEntry> {
-        let mut entries = Vec::new();
+    /// Resolves, before any writes start, which entries would overwrite a secret shared with
+    /// other labels or apps. Returns redirected secret URIs by key and drops refused entries.
+    fn resolve_import_secret_targets(
+        ctx: &ActiveKvContext,
+        entries: &mut Vec<ImportEntry>,
+        skipped: &mut usize,
+        policy: SharedSecretPolicy,
+    ) -> Option<BTreeMap<String, String>> {
+        let mut targets = BTreeMap::new();
+        if entries.iter().all(|entry| entry.reference.is_some()) {
+            return Some(targets);
+        }
 
-        for (key, value) in map {
-            if let Some(obj) = value.as_object() {
-                let value_type = value_type_from_str(obj.get("type").and_then(|v| v.as_str()));
-                let val_str = obj
-                    .get("value")
-                    .map(|v| match v {
-                        serde_json::Value::String(s) => s.clone(),
-                        other => other.to_string(),
-                    })
-                    .unwrap_or_default();
-                entries.push(ImportEntry {
-                    key: key.to_string(),
-                    value: val_str,
-                    value_type,
-                });
-            } else if let Some(val_str) = value.as_str() {
-                entries.push(ImportEntry {
-                    key: key.to_string(),
-                    value: val_str.to_string(),
-                    value_type: EntryValueType::Plain,
-                });
+        let usage = load_secret_usage(ctx)?;
+        let live_keys: BTreeSet<String> = match fetch_entries(ctx) {
+            Ok(live) => live.into_iter().map(|entry| entry.key).collect(),
+            Err(err) => {
+                eprintln!("Failed to fetch entries: {err}");
+                return None;
+            }
+        };
+
+        let label = ctx.label.as_deref();
+        let mut kept = Vec::with_capacity(entries.len());
+        for entry in entries.drain(..) {
+            let full_key = prefix_key(ctx, &entry.key);
+            let secret_uri = if entry.reference.is_some() {
+                None
+            } else if let Some(current) = usage.current(&full_key, label) {
+                Some(current.uri.clone())
+            } else if entry.value_type == EntryValueType::KeyVault && !live_keys.contains(&full_key)
+            {
+                expected_secret_uri(ctx, &full_key)
             } else {
-                entries.push(ImportEntry {
-                    key: key.to_string(),
-                    value: value.to_string(),
-                    value_type: EntryValueType::Plain,
-                });
+                None
+            };
+
+            let Some(secret_uri) = secret_uri else {
+                kept.push(entry);
+                continue;
+            };
+
+            match resolve_secret_target(ctx, &usage, &full_key, &secret_uri, policy) {
+                Some(target) => {
+                    if !target.eq_ignore_ascii_case(&secret_uri) {
+                        targets.insert(entry.key.clone(), target);
+                    }
+                    kept.push(entry);
+                }
+                None => *skipped += 1,
             }
         }
 
-        entries
+        *entries = kept;
+        Some(targets)
     }
 
-    fn process_import_entry(ctx: &ActiveKvContext, entry: &ImportEntry) -> bool {
+    fn process_import_entry(
+        ctx: &ActiveKvContext,
+        entry: &ImportEntry,
+        secret_targets: &BTreeMap<String, String>,
+    ) -> bool {
         let full_key = prefix_key(ctx, &entry.key);
 
-        if let Some(existing_entry) = show_entry(ctx, &full_key).ok() {
+        if let Some(reference) = entry.reference.as_ref() {
+            if parse_secret_uri(reference).is_none() {
+                eprintln!(
+                    "Skipping '{}' because '{}' is not a valid Key Vault reference.",
+                    entry.key, reference
+                );
+                return false;
+            }
+
+            return match write_keyvault_entry(ctx, &full_key, reference) {
+                Ok(_) => true,
+                Err(err) => {
+                    eprintln!("Failed to import '{}': {err}", entry.key);
+                    false
+                }
+            };
+        }
+
+        let secret_target = secret_targets.get(&entry.key);
+
+        if let Ok(existing_entry) = show_entry(ctx, &full_key) {
             if let Some(secret_uri) = keyvault_uri_from_entry(&existing_entry) {
-                return match set_secret_value(&secret_uri, &entry.value) {
+                let result = match secret_target {
+                    Some(target) => set_secret_value(target, &entry.value)
+                        .and_then(|_| write_keyvault_entry(ctx, &full_key, target).map(|_| ())),
+                    None => roll_secret(ctx, &full_key, &secret_uri, &entry.value).map(|_| ()),
+                };
+                return match result {
                     Ok(_) => true,
                     Err(err) => {
                         eprintln!(
@@ -2046,15 +5016,16 @@ pub mod kv {
 
         let write_result = match entry.value_type {
             EntryValueType::KeyVault => {
-                match build_keyvault_reference(ctx, &full_key, &entry.value) {
+                let secret_uri = secret_target
+                    .cloned()
+                    .or_else(|| generated_secret_uri(ctx, &full_key));
+                let Some(secret_uri) = secret_uri else {
+                    eprintln!("Skipping '{}' (keyvault type).", entry.key);
+                    return false;
+                };
+                match store_secret(&secret_uri, &entry.value) {
                     Some(secret_uri) => write_keyvault_entry(ctx, &full_key, &secret_uri),
-                    None => {
-                        eprintln!(
-                            "Skipping '{}' (keyvault type) because no Key Vault is configured.",
-                            entry.key
-                        );
-                        return false;
-                    }
+                    None => return false,
                 }
             }
             EntryValueType::Plain => write_entry(ctx, &full_key, &entry.value, None),
@@ -2076,18 +5047,6 @@ pub mod kv {
         }
     }
 
-    fn value_type_from_str(value: Option<&str>) -> EntryValueType {
-        let lower = value
-            .map(|s| s.trim().to_ascii_lowercase())
-            .unwrap_or_else(|| "plain".to_string());
-
-        match lower.as_str() {
-            "keyvault" => EntryValueType::KeyVault,
-            "prompt" => EntryValueType::Prompt,
-            _ => EntryValueType::Plain,
-        }
-    }
-
     fn prompt_value_type(key: &str) -> Option<EntryValueType> {
         #[derive(Clone)]
         enum ValueChoice {
@@ -2123,111 +5082,339 @@ pub mod kv {
         }
     }
 
-    impl EntryValueType {
-        fn label(self) -> &'static str {
-            match self {
-                EntryValueType::Plain => "[plain]",
-                EntryValueType::KeyVault => "[keyvault]",
-                EntryValueType::Prompt => "[prompt]",
-            }
-        }
-    }
-
     fn build_keyvault_reference(
         ctx: &ActiveKvContext,
         full_key: &str,
         secret_value: &str,
+        shared: SharedSecretPolicy,
     ) -> Option<String> {
-        let vault_base = match ensure_vault_base(ctx) {
-            Some(base) => base,
-            None => {
-                eprintln!("No Key Vault configured for the current application.");
-                return None;
-            }
-        };
+        let secret_uri = generated_secret_uri(ctx, full_key)?;
 
-        let secret_name = secret_name_from_key(full_key);
-        let secret_uri = format!("{}/secrets/{}", vault_base, secret_name);
+        let usage = load_secret_usage(ctx)?;
+        let target = resolve_secret_target(ctx, &usage, full_key, &secret_uri, shared)?;
+        store_secret(&target, secret_value)
+    }
+
+    /// The validated secret URI the naming template assigns to `full_key`.
+    fn generated_secret_uri(ctx: &ActiveKvContext, full_key: &str) -> Option<String> {
+        let Some(vault_base) = ensure_vault_base(ctx) else {
+            eprintln!("No Key Vault configured for the current application.");
+            return None;
+        };
 
-        if let Err(err) = create_or_update_secret(&vault_base, &secret_name, secret_value) {
-            eprintln!("Failed to create secret '{}': {}", secret_name, err);
+        let secret_name = secret_name_for(ctx, full_key);
+        if let Err(err) = validate_secret_name(&secret_name) {
+            eprintln!("{err}");
             return None;
         }
 
-        Some(secret_uri)
+        Some(format!("{}/secrets/{}", vault_base, secret_name))
+    }
+
+    fn store_secret(secret_uri: &str, secret_value: &str) -> Option<String> {
+        match set_secret_value(secret_uri, secret_value) {
+            Ok(_) => Some(secret_uri.to_string()),
+            Err(err) => {
+                eprintln!(
+                    "Failed to create secret '{}': {}",
+                    display_secret_reference(secret_uri),
+                    err
+                );
+                None
+            }
+        }
+    }
+
+    /// Key Vault references held by every key and label in the store.
+    struct SecretUsage {
+        references: Vec<SecretUser>,
+    }
+
+    struct SecretUser {
+        key: String,
+        label: Option<String>,
+        uri: String,
+        secret: String,
+    }
+
+    impl SecretUsage {
+        /// Keys other than `full_key` under `label` that reference `secret_uri`.
+        fn users(&self, full_key: &str, label: Option<&str>, secret_uri: &str) -> Vec<String> {
+            let secret = secret_identity(secret_uri);
+            self.references
+                .iter()
+                .filter(|user| user.secret == secret)
+                .filter(|user| !(user.key == full_key && user.label.as_deref() == label))
+                .map(|user| {
+                    format!(
+                        "{} (label: {})",
+                        user.key,
+                        user.label.as_deref().unwrap_or("(none)")
+                    )
+                })
+                .collect()
+        }
+
+        /// The secret currently referenced by `full_key` under `label`, if any.
+        fn current(&self, full_key: &str, label: Option<&str>) -> Option<&SecretUser> {
+            self.references
+                .iter()
+                .find(|user| user.key == full_key && user.label.as_deref() == label)
+        }
     }
 
-    fn secret_name_from_key(full_key: &str) -> String {
-        let sanitized: String = full_key
-            .chars()
-            .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+    fn load_secret_usage(ctx: &ActiveKvContext) -> Option<SecretUsage> {
+        let spinner = create_spinner("Checking Key Vault references across labels...");
+        let result = fetch_all_entries(ctx);
+        spinner.finish_and_clear();
+
+        let entries = match result {
+            Ok(entries) => entries,
+            Err(err) => {
+                eprintln!("Failed to check Key Vault references across labels: {err}");
+                return None;
+            }
+        };
+
+        let references = entries
+            .into_iter()
+            .filter_map(|entry| {
+                let uri = keyvault_uri_from_entry(&entry)?;
+                Some(SecretUser {
+                    secret: secret_identity(&uri),
+                    uri,
+                    key: entry.key,
+                    label: entry.label.filter(|label| !label.is_empty()),
+                })
+            })
             .collect();
-        sanitized.to_upper_camel_case()
+
+        Some(SecretUsage { references })
     }
 
-    fn ensure_vault_base(ctx: &ActiveKvContext) -> Option<String> {
-        let vault = ctx.keyvault.as_deref()?.trim();
-        if vault.is_empty() {
-            return None;
+    /// Decides which secret `full_key` should write to when `secret_uri` is also referenced by
+    /// other labels or apps. Returns `None` when the write must not happen.
+    fn resolve_secret_target(
+        ctx: &ActiveKvContext,
+        usage: &SecretUsage,
+        full_key: &str,
+        secret_uri: &str,
+        policy: SharedSecretPolicy,
+    ) -> Option<String> {
+        let label = ctx.label.as_deref();
+        let users = usage.users(full_key, label, secret_uri);
+        if users.is_empty() {
+            return Some(secret_uri.to_string());
         }
 
-        let normalized = if vault.starts_with("http://") || vault.starts_with("https://") {
-            vault.trim_end_matches('/').to_string()
-        } else {
-            format!("https://{}.vault.azure.net", vault.trim_end_matches('/'))
+        let key = strip_prefix(ctx, full_key);
+        eprintln!(
+            "Secret '{}' for key '{}' is also referenced by: {}",
+            display_secret_reference(secret_uri),
+            key,
+            users.join(", ")
+        );
+
+        let policy = match policy {
+            SharedSecretPolicy::Prompt => prompt_shared_secret(&key),
+            other => other,
         };
 
-        Some(normalized)
+        match policy {
+            SharedSecretPolicy::Overwrite => Some(secret_uri.to_string()),
+            SharedSecretPolicy::Scope => {
+                let scoped = label_scoped_secret_uri(ctx, full_key)?;
+                if secret_identity(&scoped) == secret_identity(secret_uri)
+                    || !usage.users(full_key, label, &scoped).is_empty()
+                {
+                    eprintln!(
+                        "Label-scoped secret '{}' is shared as well; refusing to write '{}'.",
+                        display_secret_reference(&scoped),
+                        key
+                    );
+                    return None;
+                }
+                Some(scoped)
+            }
+            SharedSecretPolicy::Prompt | SharedSecretPolicy::Refuse => {
+                eprintln!("Left the shared secret for '{}' untouched.", key);
+                None
+            }
+        }
     }
 
-    fn parse_secret_uri(uri: &str) -> Option<(String, String)> {
-        let without_scheme = uri.splitn(2, "://").nth(1)?;
-        let mut parts = without_scheme.split('/');
-        let host = parts.next()?.trim();
-        if host.is_empty() {
-            return None;
+    fn prompt_shared_secret(key: &str) -> SharedSecretPolicy {
+        let options = vec![
+            MenuItem::new(
+                SharedSecretPolicy::Scope,
+                "Create a secret scoped to this label".to_string(),
+            ),
+            MenuItem::new(
+                SharedSecretPolicy::Overwrite,
+                "Overwrite the shared secret".to_string(),
+            ),
+            MenuItem::new(SharedSecretPolicy::Refuse, "Skip this key".to_string()),
+        ];
+
+        let prompt = format!("How should '{}' be written?", key);
+        match Select::new(&prompt, options)
+            .with_starting_cursor(0)
+            .prompt()
+        {
+            Ok(choice) => choice.value,
+            Err(InquireError::OperationCanceled | InquireError::OperationInterrupted) => {
+                SharedSecretPolicy::Refuse
+            }
+            Err(err) => {
+                eprintln!("Prompt failed for '{}': {}", key, err);
+                SharedSecretPolicy::Refuse
+            }
         }
+    }
 
-        let first = parts.next()?;
-        if first != "secrets" {
+    /// Secret URI for `full_key` with the label forced into the name.
+    fn label_scoped_secret_uri(ctx: &ActiveKvContext, full_key: &str) -> Option<String> {
+        let Some(label) = ctx.label.as_deref() else {
+            eprintln!("A label-scoped secret needs a label; none is configured.");
             return None;
+        };
+
+        let mut naming = ctx.secret_naming.clone();
+        if !naming.template.contains("{label}") {
+            naming.template.push_str("-{label}");
         }
 
-        let name = parts.next()?.trim();
-        if name.is_empty() {
+        let name = naming.render(
+            ctx.app_name.as_deref(),
+            Some(label),
+            &strip_prefix(ctx, full_key),
+            &ctx.separator,
+        );
+        if let Err(err) = validate_secret_name(&name) {
+            eprintln!("{err}");
             return None;
         }
 
-        let vault_name = host.split('.').next()?.to_string();
-        Some((vault_name, name.to_string()))
+        Some(format!("{}/secrets/{}", ensure_vault_base(ctx)?, name))
     }
 
-    fn create_or_update_secret(
-        vault_base: &str,
-        secret_name: &str,
-        value: &str,
-    ) -> AzCliResult<()> {
-        let vault_name = vault_base
-            .trim_start_matches("https://")
-            .trim_start_matches("http://")
-            .split('.')
-            .next()
-            .unwrap_or(vault_base);
-
-        let _: serde_json::Value = az([
-            "keyvault",
-            "secret",
-            "set",
-            "--vault-name",
-            vault_name,
-            "--name",
-            secret_name,
-            "--value",
-            value,
-            "-o",
-            "json",
-        ])?;
-        Ok(())
+    /// Derives the secret name for `full_key` using the context's naming template.
+    fn secret_name_for(ctx: &ActiveKvContext, full_key: &str) -> String {
+        let key = strip_prefix(ctx, full_key);
+        ctx.secret_naming.render(
+            ctx.app_name.as_deref(),
+            ctx.label.as_deref(),
+            &key,
+            &ctx.separator,
+        )
+    }
+
+    /// Groups keys by the secret they reference and returns every secret that a planned key
+    /// would share with another key. Planned keys replace their existing references.
+    fn secret_collisions(
+        existing: Vec<(String, String)>,
+        planned: &[(String, String)],
+    ) -> Vec<(String, Vec<String>)> {
+        let planned_keys: BTreeSet<&str> = planned.iter().map(|(key, _)| key.as_str()).collect();
+        let mut owners: BTreeMap<String, (String, BTreeSet<String>)> = BTreeMap::new();
+
+        let existing = existing
+            .into_iter()
+            .filter(|(key, _)| !planned_keys.contains(key.as_str()));
+        for (key, secret_uri) in existing.chain(planned.iter().cloned()) {
+            let display = display_secret_reference(&secret_uri);
+            owners
+                .entry(secret_identity(&secret_uri))
+                .or_insert_with(|| (display, BTreeSet::new()))
+                .1
+                .insert(key);
+        }
+
+        owners
+            .into_values()
+            .filter(|(_, keys)| {
+                keys.len() > 1 && keys.iter().any(|key| planned_keys.contains(key.as_str()))
+            })
+            .map(|(display, keys)| (display, keys.into_iter().collect()))
+            .collect()
+    }
+
+    fn existing_secret_references(
+        ctx: &ActiveKvContext,
+        entries: &[KeyValue],
+    ) -> Vec<(String, String)> {
+        entries
+            .iter()
+            .filter_map(|entry| {
+                keyvault_uri_from_entry(entry).map(|uri| (strip_prefix(ctx, &entry.key), uri))
+            })
+            .collect()
+    }
+
+    fn print_secret_collisions(collisions: &[(String, Vec<String>)]) {
+        for (secret, keys) in collisions {
+            eprintln!(
+                "Secret '{}' would be shared by keys: {}",
+                secret,
+                keys.join(", ")
+            );
+        }
+    }
+
+    /// Refuses to write a new secret for `full_key` when another key already references it.
+    fn ensure_unique_secret(ctx: &ActiveKvContext, full_key: &str) -> bool {
+        let Some(secret_uri) = expected_secret_uri(ctx, full_key) else {
+            eprintln!("No Key Vault configured for the current application.");
+            return false;
+        };
+
+        let entries = match fetch_entries(ctx) {
+            Ok(entries) => entries,
+            Err(err) => {
+                eprintln!("Failed to check existing Key Vault references: {err}");
+                return false;
+            }
+        };
+
+        let planned = [(strip_prefix(ctx, full_key), secret_uri)];
+        let collisions = secret_collisions(existing_secret_references(ctx, &entries), &planned);
+        if collisions.is_empty() {
+            return true;
+        }
+
+        print_secret_collisions(&collisions);
+        eprintln!("Adjust the naming template with `azac keyvault naming` to keep secrets apart.");
+        false
+    }
+
+    fn ensure_vault_base(ctx: &ActiveKvContext) -> Option<String> {
+        keyvault::vault_url(ctx.keyvault.as_deref()?)
+    }
+
+    fn parse_secret_uri(uri: &str) -> Option<(String, String)> {
+        KeyVaultReference::from_uri(uri)
+            .filter(|reference| reference.is_secret())
+            .map(|reference| {
+                let vault = reference.vault().to_string();
+                (vault, reference.name)
+            })
+    }
+
+    /// The version segment of a secret URI, when the reference is pinned.
+    fn parse_secret_version(uri: &str) -> Option<String> {
+        KeyVaultReference::from_uri(uri)?.version
+    }
+
+    /// Rebuilds a secret URI pinned to `version`, or unpinned when `version` is `None`.
+    fn secret_uri_with_version(uri: &str, version: Option<&str>) -> Option<String> {
+        KeyVaultReference::from_uri(uri).map(|reference| reference.with_version(version).uri())
+    }
+
+    /// Identifies the secret behind a reference regardless of version, for comparisons.
+    fn secret_identity(secret_uri: &str) -> String {
+        KeyVaultReference::parse(secret_uri)
+            .map(|reference| reference.identity())
+            .unwrap_or_else(|| secret_uri.to_ascii_lowercase())
     }
 }
 
@@ -2271,10 +5458,7 @@ fn standard_spinner_style() -> ProgressStyle {
 }
 
 fn ensure_cache_ready() -> Option<SetupCache> {
-    let (store, cache) = match load_cache_state() {
-        Some(value) => value,
-        None => return None,
-    };
+    let (store, cache) = load_cache_state()?;
 
     if cache.is_ready() {
         return Some(cache);
@@ -1,9 +1,14 @@
+use clap::ValueEnum;
 use directories::ProjectDirs;
+use heck::{ToKebabCase, ToUpperCamelCase};
 use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf};
+use std::{collections::BTreeMap, fs, path::PathBuf};
 use thiserror::Error;
 
 const DEFAULT_SEPARATOR: &str = ":";
+const DEFAULT_SECRET_TEMPLATE: &str = "{app}-{key}";
+const DEFAULT_SECRET_SEPARATOR: &str = "-";
+const MAX_SECRET_NAME_LEN: usize = 127;
 pub const DEFAULT_APP_CONFIG_ENDPOINT: &str = "https://hml-miltech-appconfig.azconfig.io";
 
 #[derive(Debug, Error)]
@@ -21,6 +26,9 @@ pub type ContextResult<T> = Result<T, ContextError>;
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Context {
     pub active: Option<ActiveContext>,
+    /// Contexts saved under a name with `azac context save`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub saved: BTreeMap<String, ActiveContext>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -33,6 +41,11 @@ pub struct ActiveContext {
     pub separator: String,
     #[serde(default)]
     pub app: AppSelection,
+    #[serde(default)]
+    pub secret_naming: SecretNaming,
+    /// Labels where destructive commands require typing the label name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected_labels: Vec<String>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -50,6 +63,134 @@ pub struct AppSelection {
     pub keyvault_subscription: Option<String>,
 }
 
+/// How Key Vault secret names are derived from configuration keys.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SecretNaming {
+    /// Template using the `{app}`, `{label}` and `{key}` placeholders.
+    #[serde(default = "default_secret_template")]
+    pub template: String,
+    /// Replaces the key separator inside `{key}`.
+    #[serde(default = "default_secret_separator")]
+    pub separator: String,
+    #[serde(default)]
+    pub casing: SecretCasing,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecretCasing {
+    /// MyAppDbPassword
+    #[default]
+    Pascal,
+    /// my-app-db-password
+    Kebab,
+    /// myapp-db-password
+    Lower,
+    /// MYAPP-DB-PASSWORD
+    Upper,
+    /// MyApp-Db-Password
+    Preserve,
+}
+
+impl Default for SecretNaming {
+    fn default() -> Self {
+        Self {
+            template: default_secret_template(),
+            separator: default_secret_separator(),
+            casing: SecretCasing::default(),
+        }
+    }
+}
+
+impl SecretNaming {
+    /// Renders the secret name for `key`, given without the app prefix.
+    pub fn render(
+        &self,
+        app: Option<&str>,
+        label: Option<&str>,
+        key: &str,
+        key_separator: &str,
+    ) -> String {
+        let key = if key_separator.is_empty() {
+            key.to_string()
+        } else {
+            key.replace(key_separator, &self.separator)
+        };
+
+        let raw = self
+            .template
+            .replace("{app}", app.unwrap_or_default())
+            .replace("{label}", label.unwrap_or_default())
+            .replace("{key}", &key);
+
+        let words = || -> String {
+            raw.chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { ' ' })
+                .collect()
+        };
+
+        match self.casing {
+            SecretCasing::Pascal => words().to_upper_camel_case(),
+            SecretCasing::Kebab => words().to_kebab_case(),
+            SecretCasing::Lower | SecretCasing::Upper | SecretCasing::Preserve => {
+                let sanitized: String = raw
+                    .chars()
+                    .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                    .collect();
+                let trimmed = sanitized.trim_matches('-');
+                match self.casing {
+                    SecretCasing::Lower => trimmed.to_ascii_lowercase(),
+                    SecretCasing::Upper => trimmed.to_ascii_uppercase(),
+                    _ => trimmed.to_string(),
+                }
+            }
+        }
+    }
+
+    /// Checks the template and separator before they are saved.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.template.contains("{key}") {
+            return Err("Secret name template must contain the `{key}` placeholder.".to_string());
+        }
+
+        if !self
+            .separator
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(
+                "Secret name separator may only contain letters, digits and dashes.".to_string(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether `label` is one of `protected_labels`, ignoring case.
+pub fn is_protected_label(protected_labels: &[String], label: Option<&str>) -> bool {
+    label.is_some_and(|label| {
+        protected_labels
+            .iter()
+            .any(|protected| protected.eq_ignore_ascii_case(label))
+    })
+}
+
+/// Checks a rendered name against Key Vault's secret naming rules.
+pub fn validate_secret_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Secret name is empty.".to_string());
+    }
+
+    if name.len() > MAX_SECRET_NAME_LEN {
+        return Err(format!(
+            "Secret name '{name}' exceeds {MAX_SECRET_NAME_LEN} characters."
+        ));
+    }
+
+    Ok(())
+}
+
 impl Context {
     pub fn load_or_default(store: &ContextStore) -> ContextResult<Self> {
         if !store.path.exists() {
@@ -99,3 +240,11 @@ pub fn default_separator() -> String {
 pub fn default_appconfig_endpoint() -> String {
     DEFAULT_APP_CONFIG_ENDPOINT.to_string()
 }
+
+fn default_secret_template() -> String {
+    DEFAULT_SECRET_TEMPLATE.to_string()
+}
+
+fn default_secret_separator() -> String {
+    DEFAULT_SECRET_SEPARATOR.to_string()
+}
@@ -0,0 +1,135 @@
+use crate::keyvault::secret_uri_from_reference;
+use clap::ValueEnum;
+use std::{fs, path::Path};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryValueType {
+    Plain,
+    KeyVault,
+    Prompt,
+}
+
+impl EntryValueType {
+    pub fn label(self) -> &'static str {
+        match self {
+            EntryValueType::Plain => "[plain]",
+            EntryValueType::KeyVault => "[keyvault]",
+            EntryValueType::Prompt => "[prompt]",
+        }
+    }
+}
+
+/// How `azac export` writes Key Vault backed entries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SecretExport {
+    /// Write the Key Vault reference (vault/secret) instead of the secret value
+    Reference,
+    /// Resolve and write the plaintext secret value
+    Value,
+    /// Leave Key Vault entries out of the export
+    Omit,
+}
+
+#[derive(Debug)]
+pub struct ImportEntry {
+    pub key: String,
+    pub value: String,
+    pub value_type: EntryValueType,
+    pub reference: Option<String>,
+}
+
+pub fn parse_import_map(path: &Path) -> Option<Vec<ImportEntry>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(err) => {
+            eprintln!("Failed to read {}: {err}", path.display());
+            return None;
+        }
+    };
+
+    if contents.trim().is_empty() {
+        eprintln!("Import file {} is empty.", path.display());
+        return None;
+    }
+
+    match parse_yaml_entries(&contents) {
+        Ok(entries) if entries.is_empty() => {
+            eprintln!("No entries found in {}.", path.display());
+            None
+        }
+        Ok(entries) => Some(entries),
+        Err(err) => {
+            eprintln!("Failed to parse {} as YAML: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+fn parse_yaml_entries(contents: &str) -> Result<Vec<ImportEntry>, String> {
+    let value: serde_json::Value = serde_yaml::from_str(contents).map_err(|err| err.to_string())?;
+    entries_from_json_value(value)
+}
+
+fn entries_from_json_value(value: serde_json::Value) -> Result<Vec<ImportEntry>, String> {
+    let map = value
+        .as_object()
+        .ok_or_else(|| "Import file must contain a mapping of keys to values.".to_string())?;
+
+    Ok(map_to_entries(map))
+}
+
+pub fn map_to_entries(map: &serde_json::Map<String, serde_json::Value>) -> Vec<ImportEntry> {
+    let mut entries = Vec::new();
+
+    for (key, value) in map {
+        if let Some(obj) = value.as_object() {
+            let value_type = value_type_from_str(obj.get("type").and_then(|v| v.as_str()));
+            let val_str = obj
+                .get("value")
+                .map(|v| match v {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .unwrap_or_default();
+            let reference = obj
+                .get("reference")
+                .and_then(|v| v.as_str())
+                .filter(|_| value_type == EntryValueType::KeyVault)
+                .map(secret_uri_from_reference);
+            entries.push(ImportEntry {
+                key: key.to_string(),
+                value: val_str,
+                value_type,
+                reference,
+            });
+        } else if let Some(val_str) = value.as_str() {
+            entries.push(ImportEntry {
+                key: key.to_string(),
+                value: val_str.to_string(),
+                value_type: EntryValueType::Plain,
+                reference: None,
+            });
+        } else {
+            entries.push(ImportEntry {
+                key: key.to_string(),
+                value: value.to_string(),
+                value_type: EntryValueType::Plain,
+                reference: None,
+            });
+        }
+    }
+
+    entries
+}
+
+fn value_type_from_str(value: Option<&str>) -> EntryValueType {
+    let lower = value
+        .map(|s| s.trim().to_ascii_lowercase())
+        .unwrap_or_else(|| "plain".to_string());
+
+    match lower.as_str() {
+        "keyvault" => EntryValueType::KeyVault,
+        "prompt" => EntryValueType::Prompt,
+        _ => EntryValueType::Plain,
+    }
+}
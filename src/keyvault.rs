@@ -0,0 +1,522 @@
+use std::fmt;
+
+const DEFAULT_VAULT_SUFFIX: &str = "vault.azure.net";
+const APP_SERVICE_PREFIX: &str = "@Microsoft.KeyVault(";
+
+/// Kind of Key Vault object a reference points at.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReferenceKind {
+    #[default]
+    Secret,
+    Certificate,
+    Key,
+}
+
+impl ReferenceKind {
+    /// Path segment used in object identifiers.
+    pub fn segment(self) -> &'static str {
+        match self {
+            ReferenceKind::Secret => "secrets",
+            ReferenceKind::Certificate => "certificates",
+            ReferenceKind::Key => "keys",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ReferenceKind::Secret => "secret",
+            ReferenceKind::Certificate => "certificate",
+            ReferenceKind::Key => "key",
+        }
+    }
+
+    fn from_segment(segment: &str) -> Option<Self> {
+        [
+            ReferenceKind::Secret,
+            ReferenceKind::Certificate,
+            ReferenceKind::Key,
+        ]
+        .into_iter()
+        .find(|kind| kind.segment() == segment)
+    }
+
+    fn from_label(label: &str) -> Option<Self> {
+        [
+            ReferenceKind::Secret,
+            ReferenceKind::Certificate,
+            ReferenceKind::Key,
+        ]
+        .into_iter()
+        .find(|kind| kind.label() == label)
+    }
+}
+
+/// A reference to a Key Vault secret, certificate or key, independent of the syntax it was
+/// written in.
+///
+/// Accepted syntaxes:
+/// - object identifiers: `https://{vault}.vault.azure.net/{secrets|certificates|keys}/{name}[/{version}]`
+/// - App Service syntax: `@Microsoft.KeyVault(SecretUri=...)` and
+///   `@Microsoft.KeyVault(VaultName=...;SecretName=...[;SecretVersion=...])`
+/// - App Configuration JSON: `{"uri": "..."}` (or `secretUri`)
+/// - the short form written by `azac export`: `{vault}/{name}[/{version}]`, prefixed with
+///   `certificate:` or `key:` for objects other than secrets; `{vault}` is the full host for
+///   vaults outside the public cloud
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyVaultReference {
+    pub kind: ReferenceKind,
+    /// Vault host, e.g. `myvault.vault.azure.net` or a sovereign cloud host.
+    pub host: String,
+    pub name: String,
+    pub version: Option<String>,
+}
+
+impl KeyVaultReference {
+    /// Builds a secret reference from a vault name or vault URL.
+    pub fn new(vault: &str, name: &str, version: Option<&str>) -> Option<Self> {
+        let host = vault_host(vault)?;
+        let name = name.trim().trim_matches('/');
+        if name.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            kind: ReferenceKind::Secret,
+            host,
+            name: name.to_string(),
+            version: non_empty(version.map(|v| v.trim().trim_matches('/'))),
+        })
+    }
+
+    /// Parses any supported syntax.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if input.starts_with(APP_SERVICE_PREFIX) {
+            Self::from_app_service(input)
+        } else if input.starts_with('{') {
+            Self::from_json(input)
+        } else if input.contains("://") {
+            Self::from_uri(input)
+        } else {
+            Self::from_short(input)
+        }
+    }
+
+    /// Parses a secret, certificate or key identifier URI.
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        let (_, rest) = uri.trim().split_once("://")?;
+        let mut parts = rest.split('/');
+        let host = parts.next()?.trim();
+        let kind = ReferenceKind::from_segment(parts.next()?)?;
+
+        let name = parts.next()?;
+        let version = parts.next();
+        if parts.any(|part| !part.is_empty()) {
+            return None;
+        }
+
+        Self::new(&format!("https://{host}"), name, version)
+            .map(|reference| Self { kind, ..reference })
+    }
+
+    /// Parses the `@Microsoft.KeyVault(...)` syntax used by App Service and App Configuration.
+    pub fn from_app_service(value: &str) -> Option<Self> {
+        let inner = value
+            .trim()
+            .strip_prefix(APP_SERVICE_PREFIX)?
+            .strip_suffix(')')?
+            .trim();
+
+        let mut secret_uri = None;
+        let mut vault_name = None;
+        let mut secret_name = None;
+        let mut secret_version = None;
+
+        for part in inner.split(';') {
+            let Some((key, value)) = part.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "secreturi" => secret_uri = Some(value),
+                "vaultname" => vault_name = Some(value),
+                "secretname" => secret_name = Some(value),
+                "secretversion" => secret_version = Some(value),
+                _ => {}
+            }
+        }
+
+        match (secret_uri, vault_name, secret_name) {
+            (Some(uri), _, _) => Self::from_uri(uri),
+            (None, Some(vault), Some(name)) => Self::new(vault, name, secret_version),
+            _ => None,
+        }
+    }
+
+    /// Parses the JSON value App Configuration stores for Key Vault references.
+    pub fn from_json(value: &str) -> Option<Self> {
+        let json: serde_json::Value = serde_json::from_str(value).ok()?;
+        let uri = json
+            .get("uri")
+            .or_else(|| json.get("secretUri"))?
+            .as_str()?;
+        Self::from_uri(uri)
+    }
+
+    /// Parses the `[kind:]vault/name[/version]` form written by `azac export`.
+    pub fn from_short(value: &str) -> Option<Self> {
+        let value = value.trim().trim_end_matches('/');
+        let (kind, value) = match value.split_once(':') {
+            Some((label, rest)) => (ReferenceKind::from_label(label)?, rest),
+            None => (ReferenceKind::Secret, value),
+        };
+
+        let mut parts = value.split('/');
+        let vault = parts.next()?;
+        let name = parts.next()?;
+        let version = parts.next();
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Self::new(vault, name, version).map(|reference| Self { kind, ..reference })
+    }
+
+    /// Vault name, i.e. the first label of the host.
+    pub fn vault(&self) -> &str {
+        self.host.split('.').next().unwrap_or(&self.host)
+    }
+
+    /// `https://{host}`, the base URL of the vault.
+    pub fn vault_url(&self) -> String {
+        format!("https://{}", self.host)
+    }
+
+    pub fn is_secret(&self) -> bool {
+        self.kind == ReferenceKind::Secret
+    }
+
+    /// Object identifier URI, including the version when pinned.
+    pub fn uri(&self) -> String {
+        let base = format!("{}/{}/{}", self.vault_url(), self.kind.segment(), self.name);
+        match &self.version {
+            Some(version) => format!("{base}/{version}"),
+            None => base,
+        }
+    }
+
+    /// `[kind:]vault/name[/version]`, as shown in listings and exports. Vaults outside the
+    /// public cloud keep their full host so the reference still round-trips.
+    pub fn short(&self) -> String {
+        let prefix = match self.kind {
+            ReferenceKind::Secret => String::new(),
+            kind => format!("{}:", kind.label()),
+        };
+        let vault = if self.is_default_host() {
+            self.vault()
+        } else {
+            &self.host
+        };
+        match &self.version {
+            Some(version) => format!("{prefix}{vault}/{}/{version}", self.name),
+            None => format!("{prefix}{vault}/{}", self.name),
+        }
+    }
+
+    fn is_default_host(&self) -> bool {
+        self.host
+            .strip_prefix(self.vault())
+            .is_some_and(|suffix| suffix == format!(".{DEFAULT_VAULT_SUFFIX}"))
+    }
+
+    /// Case-insensitive `vault/name` identifying the secret regardless of version.
+    pub fn identity(&self) -> String {
+        format!("{}/{}", self.vault(), self.name).to_ascii_lowercase()
+    }
+
+    pub fn with_version(&self, version: Option<&str>) -> Self {
+        Self {
+            version: non_empty(version),
+            ..self.clone()
+        }
+    }
+
+    /// The JSON value App Configuration stores for the reference.
+    pub fn to_json(&self) -> String {
+        serde_json::json!({ "uri": self.uri() }).to_string()
+    }
+}
+
+impl fmt::Display for KeyVaultReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.short())
+    }
+}
+
+/// Formats a secret URI in the short `vault/name[/version]` form, falling back to the input.
+pub fn display_secret_reference(secret_uri: &str) -> String {
+    KeyVaultReference::parse(secret_uri)
+        .map(|reference| reference.short())
+        .unwrap_or_else(|| secret_uri.to_string())
+}
+
+/// Expands a reference in any supported syntax into a secret URI. Unrecognized input is
+/// returned trimmed so callers can report it.
+pub fn secret_uri_from_reference(reference: &str) -> String {
+    KeyVaultReference::parse(reference)
+        .map(|reference| reference.uri())
+        .unwrap_or_else(|| reference.trim().to_string())
+}
+
+/// Normalizes a vault name, host or URL into `https://{host}`.
+pub fn vault_url(vault: &str) -> Option<String> {
+    vault_host(vault).map(|host| format!("https://{host}"))
+}
+
+fn vault_host(vault: &str) -> Option<String> {
+    let vault = vault.trim().trim_end_matches('/');
+    let host = match vault.split_once("://") {
+        Some((_, host)) => host.split('/').next().unwrap_or_default().to_string(),
+        None if vault.contains('.') => vault.to_string(),
+        None => format!("{vault}.{DEFAULT_VAULT_SUFFIX}"),
+    };
+
+    if host.is_empty() || host.starts_with('.') {
+        None
+    } else {
+        Some(host)
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value.filter(|value| !value.is_empty()).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::{Arbitrary, Gen, quickcheck};
+
+    const CLOUD_SUFFIXES: &[&str] = &[
+        DEFAULT_VAULT_SUFFIX,
+        "vault.azure.cn",
+        "vault.usgovcloudapi.net",
+        "vault.microsoftazure.de",
+    ];
+    const LETTERS: &[char] = &['a', 'B', 'k', 'V', 'x', 'Z'];
+    const NAME_CHARS: &[char] = &['a', 'B', 'k', 'V', 'x', 'Z', '0', '7', '-'];
+    const HEX: &[char] = &['0', '3', '9', 'a', 'c', 'f'];
+    const KINDS: &[ReferenceKind] = &[
+        ReferenceKind::Secret,
+        ReferenceKind::Certificate,
+        ReferenceKind::Key,
+    ];
+
+    /// A well-formed reference with a mixed-case vault and name, in any cloud.
+    #[derive(Clone, Debug)]
+    struct Case {
+        kind: ReferenceKind,
+        vault: String,
+        suffix: &'static str,
+        name: String,
+        version: Option<String>,
+    }
+
+    impl Case {
+        fn host(&self) -> String {
+            format!("{}.{}", self.vault, self.suffix)
+        }
+
+        fn reference(&self) -> KeyVaultReference {
+            let reference =
+                KeyVaultReference::new(&self.host(), &self.name, self.version.as_deref())
+                    .expect("generated reference is valid");
+            KeyVaultReference {
+                kind: self.kind,
+                ..reference
+            }
+        }
+    }
+
+    impl Arbitrary for Case {
+        fn arbitrary(g: &mut Gen) -> Self {
+            Case {
+                kind: *g.choose(KINDS).unwrap(),
+                vault: word(g, LETTERS, NAME_CHARS, 23),
+                suffix: g.choose(CLOUD_SUFFIXES).unwrap(),
+                name: word(g, NAME_CHARS, NAME_CHARS, 40),
+                version: bool::arbitrary(g).then(|| word(g, HEX, HEX, 32)),
+            }
+        }
+    }
+
+    fn word(g: &mut Gen, first: &[char], rest: &[char], max_len: usize) -> String {
+        let len = usize::arbitrary(g) % max_len;
+        let mut word = String::new();
+        word.push(*g.choose(first).unwrap());
+        word.extend((0..len).map(|_| *g.choose(rest).unwrap()));
+        word
+    }
+
+    /// Every syntax the reference can be written in, including trailing slashes and padding.
+    fn spellings(case: &Case) -> Vec<String> {
+        let reference = case.reference();
+        let mut app_service = format!(
+            "@Microsoft.KeyVault(VaultName={}; SecretName={}",
+            case.host(),
+            case.name
+        );
+        if let Some(version) = &case.version {
+            app_service.push_str(&format!("; SecretVersion={version}"));
+        }
+        app_service.push(')');
+
+        let mut spellings = vec![
+            reference.uri(),
+            format!("{}/", reference.uri()),
+            format!("  {}\n", reference.uri()),
+            reference.short(),
+            format!("{}/", reference.short()),
+            reference.to_json(),
+            serde_json::json!({ "secretUri": reference.uri() }).to_string(),
+            format!("@Microsoft.KeyVault(SecretUri={})", reference.uri()),
+            format!("@Microsoft.KeyVault(SecretUri={}/)", reference.uri()),
+        ];
+        // Only secrets can be named by vault and secret name.
+        if reference.is_secret() {
+            spellings.push(app_service);
+        }
+        spellings
+    }
+
+    #[test]
+    fn every_syntax_round_trips() {
+        fn prop(case: Case) -> bool {
+            let reference = case.reference();
+            spellings(&case)
+                .iter()
+                .all(|input| KeyVaultReference::parse(input).as_ref() == Some(&reference))
+        }
+        quickcheck(prop as fn(Case) -> bool);
+    }
+
+    #[test]
+    fn keeps_every_component() {
+        fn prop(case: Case) -> bool {
+            let reference = case.reference();
+            let expected_uri = match &case.version {
+                Some(version) => format!(
+                    "https://{}/{}/{}/{}",
+                    case.host(),
+                    case.kind.segment(),
+                    case.name,
+                    version
+                ),
+                None => format!(
+                    "https://{}/{}/{}",
+                    case.host(),
+                    case.kind.segment(),
+                    case.name
+                ),
+            };
+
+            reference.host == case.host()
+                && reference.vault() == case.vault
+                && reference.name == case.name
+                && reference.version == case.version
+                && reference.uri() == expected_uri
+        }
+        quickcheck(prop as fn(Case) -> bool);
+    }
+
+    #[test]
+    fn short_form_names_the_vault_only_in_the_public_cloud() {
+        fn prop(case: Case) -> bool {
+            let reference = case.reference();
+            let short = match reference.kind {
+                ReferenceKind::Secret => reference.short(),
+                kind => match reference
+                    .short()
+                    .strip_prefix(&format!("{}:", kind.label()))
+                {
+                    Some(short) => short.to_string(),
+                    None => return false,
+                },
+            };
+            if case.suffix == DEFAULT_VAULT_SUFFIX {
+                short.starts_with(&format!("{}/", case.vault))
+            } else {
+                short.starts_with(&format!("{}/", case.host()))
+            }
+        }
+        quickcheck(prop as fn(Case) -> bool);
+    }
+
+    #[test]
+    fn identity_ignores_case_and_version() {
+        fn prop(case: Case) -> bool {
+            let reference = case.reference();
+            let shouted = KeyVaultReference::new(
+                &case.host().to_ascii_uppercase(),
+                &case.name.to_ascii_uppercase(),
+                Some("0123abcd"),
+            )
+            .unwrap();
+            shouted.identity() == reference.identity()
+        }
+        quickcheck(prop as fn(Case) -> bool);
+    }
+
+    #[test]
+    fn parses_certificate_and_key_kinds() {
+        let certificate =
+            KeyVaultReference::parse("https://myvault.vault.azure.net/certificates/tls/v1")
+                .unwrap();
+        let key = KeyVaultReference::parse("https://myvault.vault.azure.net/keys/signing").unwrap();
+
+        assert_eq!(certificate.kind, ReferenceKind::Certificate);
+        assert_eq!(certificate.short(), "certificate:myvault/tls/v1");
+        assert_eq!(key.kind, ReferenceKind::Key);
+        assert_eq!(key.short(), "key:myvault/signing");
+        assert!(!key.is_secret());
+    }
+
+    #[test]
+    fn keeps_sovereign_cloud_hosts() {
+        let reference =
+            KeyVaultReference::parse("https://myvault.vault.azure.cn/secrets/db-password/abc123")
+                .unwrap();
+
+        assert_eq!(reference.host, "myvault.vault.azure.cn");
+        assert_eq!(
+            reference.short(),
+            "myvault.vault.azure.cn/db-password/abc123"
+        );
+        assert_eq!(
+            KeyVaultReference::parse(&reference.short()),
+            Some(reference)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_references() {
+        assert_eq!(KeyVaultReference::parse("myvault"), None);
+        assert_eq!(
+            KeyVaultReference::parse("https://myvault.vault.azure.net/blobs/x"),
+            None
+        );
+        assert_eq!(
+            KeyVaultReference::parse("https://myvault.vault.azure.net/secrets/a/b/c"),
+            None
+        );
+        assert_eq!(
+            KeyVaultReference::parse("@Microsoft.KeyVault(VaultName=myvault)"),
+            None
+        );
+        assert_eq!(KeyVaultReference::from_json("{}"), None);
+    }
+}
@@ -3,10 +3,16 @@ mod cache;
 mod commands;
 mod context;
 mod convert;
+mod entries;
+mod keyvault;
+mod rotate;
+mod serve;
 
-use clap::{Parser, Subcommand};
+use clap::{ArgGroup, Parser, Subcommand};
 use commands::kv;
-use convert::ConvertCommand;
+use context::SecretCasing;
+use convert::{ConvertCommand, EnvNaming, Shell, SourceOptions};
+use rotate::RotationPolicy;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -31,7 +37,7 @@ enum Command {
     /// List keys for the current App Configuration/App context
     #[command(alias = "ls")]
     List,
-    /// Show a key by name
+    /// Show a key by name, or every key matching a `*` pattern
     Show { key: String },
     /// Set a key/value pair (optionally storing the value in Key Vault)
     Set {
@@ -39,27 +45,274 @@ enum Command {
         value: String,
         #[arg(long)]
         keyvault: bool,
+        /// What to do when the secret is also referenced by other labels or apps
+        #[arg(long, value_enum, default_value_t)]
+        shared_secrets: kv::SharedSecretPolicy,
     },
-    /// Delete a key
+    /// Delete keys; `*` matches any characters (e.g. `Legacy:*`)
     Delete {
         #[arg(required = true)]
         keys: Vec<String>,
+        /// Skip the confirmation prompt
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Rename a key, or a subtree with `Old:*` `New:*`
+    #[command(alias = "move")]
+    Mv {
+        from: String,
+        to: String,
+        /// Also copy Key Vault secrets to the name derived from the new key
+        #[arg(long)]
+        rename_secret: bool,
+        /// What to do when a renamed secret is also referenced by other labels or apps
+        #[arg(long, value_enum, default_value_t, requires = "rename_secret")]
+        shared_secrets: kv::SharedSecretPolicy,
+        /// Show the plan without moving
+        #[arg(long)]
+        dry_run: bool,
+        /// Skip the confirmation prompt
+        #[arg(long)]
+        yes: bool,
     },
     /// Compare the current configuration against a saved export
-    Plan { file: PathBuf },
-    /// Export configuration data as YAML
-    Export { file: PathBuf },
+    Plan {
+        file: PathBuf,
+        #[command(flatten)]
+        source: SourceOptions,
+    },
+    /// Export configuration data as YAML or another format
+    Export {
+        /// Destination file, or `-` to write to stdout
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t = kv::ExportFormat::Yaml)]
+        format: kv::ExportFormat,
+        /// How Key Vault entries are written
+        #[arg(long, value_enum, default_value_t = entries::SecretExport::Reference)]
+        secrets: entries::SecretExport,
+    },
     /// Import configuration data from a file
-    Import { file: PathBuf },
-    /// Promote a plain value to a Key Vault reference
-    Promote { key: String },
-    /// Demote a Key Vault reference to a plain value
-    Demote { key: String },
+    Import {
+        file: PathBuf,
+        #[command(flatten)]
+        source: SourceOptions,
+        /// What to do when a secret is also referenced by other labels or apps
+        #[arg(long, value_enum, default_value_t)]
+        shared_secrets: kv::SharedSecretPolicy,
+        /// Skip the confirmation prompt before overwriting existing keys
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Copy entries to another label, app prefix or App Configuration store
+    Copy {
+        /// Keys to copy; `*` matches any characters (all keys by default)
+        keys: Vec<String>,
+        /// Saved context to copy into (see `azac context save`)
+        #[arg(long)]
+        to_context: Option<String>,
+        /// App Configuration store to copy into
+        #[arg(long)]
+        to_store: Option<String>,
+        /// App prefix to copy into
+        #[arg(long)]
+        to_app: Option<String>,
+        /// Label to copy into (empty for no label)
+        #[arg(long)]
+        to_label: Option<String>,
+        /// Key Vault receiving copied secrets
+        #[arg(long)]
+        to_keyvault: Option<String>,
+        /// How Key Vault entries are copied
+        #[arg(long, value_enum, default_value_t)]
+        secrets: kv::CopySecrets,
+        /// What to do when a copied secret is also referenced by other labels or apps
+        #[arg(long, value_enum, default_value_t)]
+        shared_secrets: kv::SharedSecretPolicy,
+        /// Show the plan without copying
+        #[arg(long)]
+        dry_run: bool,
+        /// Skip the confirmation prompt
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Compare the app's configuration across labels, apps or saved contexts
+    #[command(group(ArgGroup::new("environments").required(true)))]
+    Diff {
+        /// Labels to compare, e.g. `dev,staging,prod`
+        #[arg(long, value_delimiter = ',', group = "environments")]
+        labels: Vec<String>,
+        /// App prefixes to compare under the active label
+        #[arg(long, value_delimiter = ',', group = "environments")]
+        apps: Vec<String>,
+        /// Saved contexts to compare (see `azac context save`)
+        #[arg(long, value_delimiter = ',', group = "environments")]
+        contexts: Vec<String>,
+        /// Compare Key Vault secret values instead of their references
+        #[arg(long)]
+        resolve_secrets: bool,
+        /// Show every key with its values instead of only the differences
+        #[arg(long)]
+        full: bool,
+    },
+    /// Promote plain values to Key Vault references; `*` matches any characters
+    Promote {
+        key: String,
+        /// What to do when the secret is also referenced by other labels or apps
+        #[arg(long, value_enum, default_value_t)]
+        shared_secrets: kv::SharedSecretPolicy,
+    },
+    /// Demote Key Vault references to plain values; `*` matches any characters
+    Demote {
+        key: String,
+        /// Skip the confirmation prompt
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Run a command with the app's configuration as environment variables
+    Run {
+        /// How configuration keys are mapped to environment variable names
+        #[arg(long, value_enum, default_value_t)]
+        naming: EnvNaming,
+        /// Command to run, followed by its arguments
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+    /// Print shell export statements for the app's configuration
+    Env {
+        #[arg(long, value_enum, default_value_t = Shell::Bash)]
+        shell: Shell,
+        /// How configuration keys are mapped to environment variable names
+        #[arg(long, value_enum, default_value_t)]
+        naming: EnvNaming,
+        /// Resolve and include Key Vault secret values (omitted by default)
+        #[arg(long)]
+        include_secrets: bool,
+    },
+    /// Serve configuration locally through the App Configuration REST API
+    ///
+    /// The server keeps no history: /revisions lists only the current revision of each key.
+    Serve {
+        /// azac YAML file to serve (defaults to a live snapshot of the active app)
+        file: Option<PathBuf>,
+        #[arg(long, default_value_t = 8483)]
+        port: u16,
+        /// Label assigned to entries loaded from FILE
+        #[arg(long)]
+        label: Option<String>,
+        /// Prefix prepended to keys loaded from FILE (e.g. `MyApp:`)
+        #[arg(long)]
+        prefix: Option<String>,
+        /// YAML map of secret names to values served under /secrets
+        #[arg(long, value_name = "FILE")]
+        secrets: Option<PathBuf>,
+    },
     /// Convert configuration files into the azac YAML schema
     Convert {
         #[command(subcommand)]
         target: ConvertCommand,
     },
+    /// Validate the active app's configuration
+    Check {
+        #[command(subcommand)]
+        target: CheckCommand,
+    },
+    /// Replace a Key Vault secret with a newly generated value
+    Rotate {
+        key: String,
+        #[command(flatten)]
+        policy: RotationPolicy,
+    },
+    /// Inspect and manage the secret versions behind Key Vault entries
+    #[command(alias = "secrets")]
+    Secret {
+        #[command(subcommand)]
+        command: SecretCommand,
+    },
+    /// Save, list and switch between named contexts
+    Context {
+        #[command(subcommand)]
+        command: ContextCommand,
+    },
+    /// Manage Key Vault secrets backing configuration entries
+    Keyvault {
+        #[command(subcommand)]
+        command: KeyvaultCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum CheckCommand {
+    /// Resolve every Key Vault reference and report broken ones
+    Refs,
+}
+
+#[derive(Subcommand)]
+enum SecretCommand {
+    /// List the versions of a key's secret
+    Versions { key: String },
+    /// Pin a key's reference to a secret version (the latest by default)
+    Pin {
+        key: String,
+        version: Option<String>,
+    },
+    /// Make a key's reference follow the latest secret version
+    Unpin { key: String },
+    /// Write a new secret version and move a pinned reference to it
+    Roll { key: String, value: String },
+    /// List the app's secrets that expire soon
+    Report {
+        /// Report secrets expiring within this many days
+        #[arg(long, default_value_t = 30)]
+        days: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum ContextCommand {
+    /// Save the active context under a name
+    Save { name: String },
+    /// List saved contexts
+    #[command(alias = "ls")]
+    List,
+    /// Make a saved context the active one
+    Use { name: String },
+    /// Forget a saved context
+    Remove { name: String },
+    /// Require typing the label name before destructive commands on it
+    Protect { label: String },
+    /// Stop protecting a label
+    Unprotect { label: String },
+}
+
+#[derive(Subcommand)]
+enum KeyvaultCommand {
+    /// Show or change how secret names are derived from keys
+    Naming {
+        /// Name template using `{app}`, `{label}` and `{key}`
+        #[arg(long)]
+        template: Option<String>,
+        /// Replacement for the key separator inside `{key}`
+        #[arg(long, allow_hyphen_values = true)]
+        separator: Option<String>,
+        #[arg(long, value_enum)]
+        casing: Option<SecretCasing>,
+        /// Key to render as an example
+        #[arg(long)]
+        preview: Option<String>,
+    },
+    /// List secrets in the configured vault that no key references
+    ///
+    /// Only secrets azac created, which carry the managed-by=azac tag, are considered. A vault
+    /// shared with other stores or apps may hold secrets they still use, so review the list
+    /// before deleting.
+    Gc {
+        /// Delete the unreferenced secrets
+        #[arg(long)]
+        delete: bool,
+        /// Skip the yes/no prompt; stores with protected labels still require typing the vault name
+        #[arg(long, requires = "delete")]
+        yes: bool,
+    },
 }
 
 fn main() {
@@ -74,13 +327,117 @@ fn main() {
             key,
             value,
             keyvault,
-        } => kv::set_key(&key, &value, keyvault),
-        Command::Promote { key } => kv::promote_key(&key),
-        Command::Demote { key } => kv::demote_key(&key),
-        Command::Delete { keys } => kv::delete_keys(&keys),
-        Command::Plan { file } => kv::plan(&file),
-        Command::Export { file } => kv::export_entries(&file),
-        Command::Import { file } => kv::import_entries(&file),
+            shared_secrets,
+        } => kv::set_key(&key, &value, keyvault, shared_secrets),
+        Command::Promote {
+            key,
+            shared_secrets,
+        } => kv::promote_key(&key, shared_secrets),
+        Command::Demote { key, yes } => kv::demote_key(&key, yes),
+        Command::Delete { keys, yes } => kv::delete_keys(&keys, yes),
+        Command::Mv {
+            from,
+            to,
+            rename_secret,
+            shared_secrets,
+            dry_run,
+            yes,
+        } => kv::move_keys(&from, &to, rename_secret, shared_secrets, dry_run, yes),
+        Command::Plan { file, source } => kv::plan(&file, &source),
+        Command::Export {
+            file,
+            format,
+            secrets,
+        } => kv::export_entries(&file, format, secrets),
+        Command::Import {
+            file,
+            source,
+            shared_secrets,
+            yes,
+        } => kv::import_entries(&file, &source, shared_secrets, yes),
+        Command::Copy {
+            keys,
+            to_context,
+            to_store,
+            to_app,
+            to_label,
+            to_keyvault,
+            secrets,
+            shared_secrets,
+            dry_run,
+            yes,
+        } => kv::copy_entries(&kv::CopyOptions {
+            keys,
+            to_context,
+            to_store,
+            to_app,
+            to_label,
+            to_keyvault,
+            secrets,
+            shared_secrets,
+            dry_run,
+            yes,
+        }),
+        Command::Diff {
+            labels,
+            apps,
+            contexts,
+            resolve_secrets,
+            full,
+        } => kv::diff_environments(&kv::DiffOptions {
+            labels,
+            apps,
+            contexts,
+            resolve_secrets,
+            full,
+        }),
+        Command::Run { naming, command } => kv::run_with_env(&command, naming),
+        Command::Env {
+            shell,
+            naming,
+            include_secrets,
+        } => kv::print_env(shell, naming, include_secrets),
+        Command::Serve {
+            file,
+            port,
+            label,
+            prefix,
+            secrets,
+        } => serve::run(serve::ServeOptions {
+            file,
+            port,
+            label,
+            prefix,
+            secrets,
+        }),
         Command::Convert { target } => convert::run(target),
+        Command::Check { target } => match target {
+            CheckCommand::Refs => kv::check_refs(),
+        },
+        Command::Rotate { key, policy } => kv::rotate_key(&key, &policy),
+        Command::Secret { command } => match command {
+            SecretCommand::Versions { key } => kv::secret_versions(&key),
+            SecretCommand::Pin { key, version } => kv::pin_secret(&key, version.as_deref()),
+            SecretCommand::Unpin { key } => kv::unpin_secret(&key),
+            SecretCommand::Roll { key, value } => kv::roll_secret_key(&key, &value),
+            SecretCommand::Report { days } => kv::secrets_report(days),
+        },
+        Command::Context { command } => match command {
+            ContextCommand::Save { name } => commands::save_context_as(&name),
+            ContextCommand::List => commands::list_contexts(),
+            ContextCommand::Use { name } => commands::use_context(&name),
+            ContextCommand::Remove { name } => commands::remove_context(&name),
+            ContextCommand::Protect { label } => commands::protect_label(&label, true),
+            ContextCommand::Unprotect { label } => commands::protect_label(&label, false),
+        },
+        Command::Keyvault { command } => match command {
+            KeyvaultCommand::Naming {
+                template,
+                separator,
+                casing,
+                preview,
+            } => kv::configure_secret_naming(template, separator, casing, preview.as_deref()),
+            KeyvaultCommand::Gc { delete, yes } => kv::keyvault_gc(delete, yes),
+        },
     }
 }
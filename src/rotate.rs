@@ -0,0 +1,169 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{Duration, Utc};
+use clap::{Args, ValueEnum};
+use rand::{Rng, RngCore, seq::SliceRandom};
+
+const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const DIGITS: &[u8] = b"0123456789";
+const SYMBOLS: &[u8] = b"!#$%&*+-.:=?@^_~";
+
+/// Shape of the generated secret value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ValueFormat {
+    /// Random characters drawn from the selected classes
+    #[default]
+    Password,
+    /// Base64 encoding of LENGTH random bytes
+    Base64,
+    /// LENGTH hexadecimal characters
+    Hex,
+    /// Random (v4) UUID; LENGTH is ignored
+    Uuid,
+}
+
+/// Character classes used by the `password` format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CharClass {
+    Upper,
+    Lower,
+    Digits,
+    Symbols,
+}
+
+#[derive(Args, Debug)]
+pub struct RotationPolicy {
+    /// Format of the generated value
+    #[arg(long, value_enum, default_value_t)]
+    pub format: ValueFormat,
+    /// Length of the generated value (bytes for base64)
+    #[arg(long, default_value_t = 32)]
+    pub length: usize,
+    /// Character classes for the password format; each appears at least once
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [CharClass::Upper, CharClass::Lower, CharClass::Digits]
+    )]
+    pub classes: Vec<CharClass>,
+    /// Keep the previous version enabled for this long (e.g. `24h`, `7d`); the next rotate after
+    /// that disables it
+    #[arg(long, value_parser = parse_grace)]
+    pub grace: Option<Duration>,
+}
+
+impl CharClass {
+    fn alphabet(self) -> &'static [u8] {
+        match self {
+            CharClass::Upper => UPPER,
+            CharClass::Lower => LOWER,
+            CharClass::Digits => DIGITS,
+            CharClass::Symbols => SYMBOLS,
+        }
+    }
+}
+
+/// Generates a new secret value according to `policy`.
+pub fn generate(policy: &RotationPolicy) -> Result<String, String> {
+    let mut rng = rand::thread_rng();
+
+    match policy.format {
+        ValueFormat::Password => {
+            let mut classes: Vec<CharClass> = Vec::new();
+            for class in &policy.classes {
+                if !classes.contains(class) {
+                    classes.push(*class);
+                }
+            }
+            if classes.is_empty() {
+                return Err("Select at least one character class.".to_string());
+            }
+            if policy.length < classes.len() {
+                return Err(format!(
+                    "Length {} is too short to include {} character classes.",
+                    policy.length,
+                    classes.len()
+                ));
+            }
+
+            let alphabet: Vec<u8> = classes
+                .iter()
+                .flat_map(|class| class.alphabet().iter().copied())
+                .collect();
+            let mut chars: Vec<u8> = classes
+                .iter()
+                .map(|class| {
+                    let set = class.alphabet();
+                    set[rng.gen_range(0..set.len())]
+                })
+                .collect();
+            while chars.len() < policy.length {
+                chars.push(alphabet[rng.gen_range(0..alphabet.len())]);
+            }
+            chars.shuffle(&mut rng);
+
+            Ok(chars.into_iter().map(char::from).collect())
+        }
+        ValueFormat::Base64 => {
+            if policy.length == 0 {
+                return Err("Length must be greater than zero.".to_string());
+            }
+            let mut bytes = vec![0u8; policy.length];
+            rng.fill_bytes(&mut bytes);
+            Ok(STANDARD.encode(bytes))
+        }
+        ValueFormat::Hex => {
+            if policy.length == 0 {
+                return Err("Length must be greater than zero.".to_string());
+            }
+            Ok((0..policy.length)
+                .map(|_| char::from(b"0123456789abcdef"[rng.gen_range(0..16)]))
+                .collect())
+        }
+        ValueFormat::Uuid => {
+            let mut bytes = [0u8; 16];
+            rng.fill_bytes(&mut bytes);
+            bytes[6] = (bytes[6] & 0x0f) | 0x40;
+            bytes[8] = (bytes[8] & 0x3f) | 0x80;
+            let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+            Ok(format!(
+                "{}-{}-{}-{}-{}",
+                &hex[0..8],
+                &hex[8..12],
+                &hex[12..16],
+                &hex[16..20],
+                &hex[20..32]
+            ))
+        }
+    }
+}
+
+/// Parses a grace period such as `90m`, `24h` or `7d`. A bare number is read as days.
+fn parse_grace(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let (amount, unit) = match input.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((index, _)) => input.split_at(index),
+        None => (input, "d"),
+    };
+
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("Invalid grace period '{input}'."))?;
+
+    let grace = match unit {
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        _ => {
+            return Err(format!(
+                "Invalid grace period unit in '{input}'; use m, h or d."
+            ));
+        }
+    };
+
+    // The expiry written to the previous version is now + grace, so that must fit as well.
+    grace
+        .filter(|grace| Utc::now().checked_add_signed(*grace).is_some())
+        .ok_or_else(|| format!("Grace period '{input}' is too long."))
+}
@@ -0,0 +1,536 @@
+use crate::{
+    commands::kv::{self, KeyValue},
+    context::{ActiveContext, Context, ContextStore, default_separator},
+    entries::{self, EntryValueType},
+    keyvault::KeyVaultReference,
+};
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const KEYVAULT_REF_CONTENT_TYPE: &str =
+    "application/vnd.microsoft.appconfig.keyvaultref+json;charset=utf-8";
+const KV_CONTENT_TYPE: &str = "application/vnd.microsoft.appconfig.kv+json; charset=utf-8";
+const KVSET_CONTENT_TYPE: &str = "application/vnd.microsoft.appconfig.kvset+json; charset=utf-8";
+const KEYSET_CONTENT_TYPE: &str = "application/vnd.microsoft.appconfig.keyset+json; charset=utf-8";
+const LABELSET_CONTENT_TYPE: &str =
+    "application/vnd.microsoft.appconfig.labelset+json; charset=utf-8";
+const PROBLEM_CONTENT_TYPE: &str = "application/problem+json; charset=utf-8";
+const JSON_CONTENT_TYPE: &str = "application/json; charset=utf-8";
+
+pub struct ServeOptions {
+    /// azac YAML file to serve; the live App Configuration snapshot is used when absent.
+    pub file: Option<PathBuf>,
+    pub port: u16,
+    /// Label applied to entries loaded from a file.
+    pub label: Option<String>,
+    /// Prefix prepended to keys loaded from a file, e.g. `MyApp:`.
+    pub prefix: Option<String>,
+    /// YAML map of secret names to values served under `/secrets/{name}`.
+    pub secrets: Option<PathBuf>,
+}
+
+pub fn run(options: ServeOptions) {
+    if let Err(err) = serve(options) {
+        eprintln!("{}", err);
+    }
+}
+
+fn serve(options: ServeOptions) -> Result<(), ServeError> {
+    let base_url = format!("http://127.0.0.1:{}", options.port);
+
+    let mut secrets = match options.secrets.as_deref() {
+        Some(path) => load_secrets(path)?,
+        None => BTreeMap::new(),
+    };
+
+    let settings = match options.file.as_deref() {
+        Some(path) => settings_from_file(path, &options, &base_url, &mut secrets)?,
+        None => settings_from_live()?,
+    };
+
+    let store = Store { settings, secrets };
+
+    let server = Server::http(("127.0.0.1", options.port))
+        .map_err(|err| ServeError::Bind(options.port, err.to_string()))?;
+
+    println!(
+        "Serving {} key-values and {} secrets on {} (Ctrl+C to stop).",
+        store.settings.len(),
+        store.secrets.len(),
+        base_url
+    );
+
+    for request in server.incoming_requests() {
+        handle_request(&store, &base_url, request);
+    }
+
+    Ok(())
+}
+
+struct Store {
+    settings: Vec<Setting>,
+    secrets: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct Setting {
+    etag: String,
+    key: String,
+    label: Option<String>,
+    content_type: Option<String>,
+    value: Option<String>,
+    tags: BTreeMap<String, String>,
+    locked: bool,
+    last_modified: String,
+}
+
+impl Setting {
+    fn new(
+        key: String,
+        label: Option<String>,
+        value: Option<String>,
+        content_type: Option<String>,
+    ) -> Self {
+        Self {
+            etag: content_etag(
+                &key,
+                label.as_deref(),
+                value.as_deref(),
+                content_type.as_deref(),
+            ),
+            key,
+            label,
+            content_type,
+            value,
+            tags: BTreeMap::new(),
+            locked: false,
+            last_modified: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+        }
+    }
+}
+
+/// SHA-256 over the fields that make up a setting, so the ETag only changes with its content
+/// and stays the same across restarts.
+fn content_etag(
+    key: &str,
+    label: Option<&str>,
+    value: Option<&str>,
+    content_type: Option<&str>,
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(key);
+    for field in [label, value, content_type] {
+        // Tag each field so that `None` and `Some("")` hash differently.
+        match field {
+            Some(field) => {
+                hasher.update([1]);
+                hasher.update((field.len() as u64).to_be_bytes());
+                hasher.update(field);
+            }
+            None => hasher.update([0]),
+        }
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn load_secrets(path: &Path) -> Result<BTreeMap<String, String>, ServeError> {
+    let contents = fs::read_to_string(path).map_err(|err| ServeError::Io(path.into(), err))?;
+    if contents.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    serde_yaml::from_str(&contents).map_err(|err| ServeError::Parse(path.into(), err))
+}
+
+fn settings_from_file(
+    path: &Path,
+    options: &ServeOptions,
+    base_url: &str,
+    secrets: &mut BTreeMap<String, String>,
+) -> Result<Vec<Setting>, ServeError> {
+    let entries = entries::parse_import_map(path).ok_or(ServeError::Snapshot)?;
+    let prefix = options.prefix.as_deref().unwrap_or_default();
+    let label = options.label.clone().filter(|label| !label.is_empty());
+
+    // Secrets kept in the file get the names the active context would give them in Key Vault.
+    let context = active_context();
+    let naming = context
+        .as_ref()
+        .map(|context| context.secret_naming.clone())
+        .unwrap_or_default();
+    let app = context
+        .as_ref()
+        .and_then(|context| context.app.name.clone());
+    let separator = context
+        .map(|context| context.separator)
+        .unwrap_or_else(default_separator);
+
+    let settings = entries
+        .into_iter()
+        .map(|entry| {
+            let key = format!("{prefix}{}", entry.key);
+
+            if entry.value_type != EntryValueType::KeyVault {
+                return Setting::new(key, label.clone(), Some(entry.value), None);
+            }
+
+            // Plaintext secrets in the file are served through the local secrets endpoint.
+            let reference = match entry
+                .reference
+                .as_deref()
+                .and_then(KeyVaultReference::parse)
+            {
+                Some(reference) => reference.to_json(),
+                None => {
+                    let name =
+                        naming.render(app.as_deref(), label.as_deref(), &entry.key, &separator);
+                    secrets.entry(name.clone()).or_insert(entry.value);
+                    json!({ "uri": format!("{base_url}/secrets/{name}") }).to_string()
+                }
+            };
+
+            Setting::new(
+                key,
+                label.clone(),
+                Some(reference),
+                Some(KEYVAULT_REF_CONTENT_TYPE.to_string()),
+            )
+        })
+        .collect();
+
+    Ok(settings)
+}
+
+fn active_context() -> Option<ActiveContext> {
+    ContextStore::new()
+        .ok()
+        .and_then(|store| Context::load_or_default(&store).ok())
+        .and_then(|context| context.active)
+}
+
+fn settings_from_live() -> Result<Vec<Setting>, ServeError> {
+    let entries = kv::fetch_active_entries().ok_or(ServeError::Snapshot)?;
+
+    Ok(entries
+        .into_iter()
+        .map(
+            |KeyValue {
+                 key,
+                 label,
+                 value,
+                 content_type,
+                 tags,
+             }| Setting {
+                tags: tags.unwrap_or_default(),
+                ..Setting::new(
+                    key,
+                    label.filter(|label| !label.is_empty()),
+                    value,
+                    content_type.filter(|ct| !ct.is_empty()),
+                )
+            },
+        )
+        .collect())
+}
+
+fn handle_request(store: &Store, base_url: &str, request: Request) {
+    if !matches!(request.method(), Method::Get | Method::Head) {
+        let body = problem(405, "Method Not Allowed", "The local server is read-only.");
+        respond(request, 405, PROBLEM_CONTENT_TYPE, &body, None);
+        return;
+    }
+
+    let (path, query) = split_url(request.url());
+    let segments: Vec<String> = path
+        .trim_matches('/')
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(percent_decode)
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+    match segments.as_slice() {
+        // Nothing is versioned locally, so the only revision of a key is its current value.
+        ["kv"] | ["revisions"] => {
+            let items: Vec<&Setting> = store
+                .settings
+                .iter()
+                .filter(|setting| matches_query(setting, &query))
+                .collect();
+            respond(
+                request,
+                200,
+                KVSET_CONTENT_TYPE,
+                &json!({ "items": items }),
+                None,
+            );
+        }
+        ["kv", key] => {
+            let label =
+                query_value(&query, "label").filter(|label| !label.is_empty() && *label != "\0");
+            let found = store
+                .settings
+                .iter()
+                .find(|setting| setting.key == *key && setting.label.as_deref() == label);
+
+            let Some(setting) = found else {
+                let body = problem(404, "Not Found", &format!("Key '{key}' was not found."));
+                respond(request, 404, PROBLEM_CONTENT_TYPE, &body, None);
+                return;
+            };
+
+            let if_none_match = header_value(&request, "If-None-Match");
+            if if_none_match.is_some_and(|etag| etag_matches(&etag, &setting.etag)) {
+                respond(
+                    request,
+                    304,
+                    KV_CONTENT_TYPE,
+                    &Value::Null,
+                    Some(&setting.etag),
+                );
+                return;
+            }
+
+            let if_match = header_value(&request, "If-Match");
+            if if_match.is_some_and(|etag| !etag_matches(&etag, &setting.etag)) {
+                let body = problem(412, "Precondition Failed", "ETag does not match.");
+                respond(request, 412, PROBLEM_CONTENT_TYPE, &body, None);
+                return;
+            }
+
+            let body = json!(setting);
+            let etag = setting.etag.clone();
+            respond(request, 200, KV_CONTENT_TYPE, &body, Some(&etag));
+        }
+        ["keys"] => {
+            let filter = query_value(&query, "name");
+            let names: BTreeSet<&str> = store
+                .settings
+                .iter()
+                .map(|setting| setting.key.as_str())
+                .filter(|key| filter.is_none_or(|filter| matches_filter(filter, Some(key))))
+                .collect();
+            let items: Vec<Value> = names
+                .into_iter()
+                .map(|name| json!({ "name": name }))
+                .collect();
+            respond(
+                request,
+                200,
+                KEYSET_CONTENT_TYPE,
+                &json!({ "items": items }),
+                None,
+            );
+        }
+        ["labels"] => {
+            let filter = query_value(&query, "name");
+            let labels: BTreeSet<Option<&str>> = store
+                .settings
+                .iter()
+                .map(|setting| setting.label.as_deref())
+                .filter(|label| filter.is_none_or(|filter| matches_filter(filter, *label)))
+                .collect();
+            let items: Vec<Value> = labels
+                .into_iter()
+                .map(|name| json!({ "name": name }))
+                .collect();
+            respond(
+                request,
+                200,
+                LABELSET_CONTENT_TYPE,
+                &json!({ "items": items }),
+                None,
+            );
+        }
+        ["secrets", name] | ["secrets", name, _] => match store.secrets.get(*name) {
+            Some(value) => {
+                let body = json!({
+                    "value": value,
+                    "id": format!("{base_url}/secrets/{name}"),
+                    "attributes": { "enabled": true },
+                });
+                respond(request, 200, JSON_CONTENT_TYPE, &body, None);
+            }
+            None => {
+                let body = problem(404, "Not Found", &format!("Secret '{name}' was not found."));
+                respond(request, 404, PROBLEM_CONTENT_TYPE, &body, None);
+            }
+        },
+        _ => {
+            let body = problem(404, "Not Found", &format!("No route for '{path}'."));
+            respond(request, 404, PROBLEM_CONTENT_TYPE, &body, None);
+        }
+    }
+}
+
+fn respond(request: Request, status: u16, content_type: &str, body: &Value, etag: Option<&str>) {
+    let payload = if body.is_null() {
+        String::new()
+    } else {
+        body.to_string()
+    };
+
+    let mut response = Response::from_string(payload).with_status_code(status);
+    if let Ok(header) = Header::from_bytes("Content-Type", content_type) {
+        response.add_header(header);
+    }
+    if let Some(etag) = etag
+        && let Ok(header) = Header::from_bytes("ETag", format!("\"{etag}\""))
+    {
+        response.add_header(header);
+    }
+
+    if let Err(err) = request.respond(response) {
+        eprintln!("Failed to send response: {err}");
+    }
+}
+
+fn problem(status: u16, title: &str, detail: &str) -> Value {
+    json!({
+        "type": "about:blank",
+        "title": title,
+        "status": status,
+        "detail": detail,
+    })
+}
+
+fn header_value(request: &Request, name: &str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.to_string().eq_ignore_ascii_case(name))
+        .map(|header| header.value.as_str().to_string())
+}
+
+fn etag_matches(header: &str, etag: &str) -> bool {
+    header
+        .split(',')
+        .map(|candidate| candidate.trim().trim_matches('"'))
+        .any(|candidate| candidate == "*" || candidate == etag)
+}
+
+fn matches_query(setting: &Setting, query: &[(String, String)]) -> bool {
+    let key_ok =
+        query_value(query, "key").is_none_or(|filter| matches_filter(filter, Some(&setting.key)));
+    let label_ok = query_value(query, "label")
+        .is_none_or(|filter| matches_filter(filter, setting.label.as_deref()));
+    key_ok && label_ok
+}
+
+/// Evaluates an App Configuration filter: comma-separated alternatives where a trailing
+/// `*` matches a prefix and `\0` matches the null label. `\` escapes `,` and `*`.
+fn matches_filter(filter: &str, candidate: Option<&str>) -> bool {
+    split_filter(filter).iter().any(|(pattern, wildcard)| {
+        if pattern == "\0" {
+            return candidate.is_none();
+        }
+
+        match (candidate, wildcard) {
+            (_, true) if pattern.is_empty() => true,
+            (Some(value), true) => value.starts_with(pattern.as_str()),
+            (Some(value), false) => value == pattern,
+            (None, _) => false,
+        }
+    })
+}
+
+fn split_filter(filter: &str) -> Vec<(String, bool)> {
+    let mut patterns = Vec::new();
+    let mut current = String::new();
+    let mut wildcard = false;
+    let mut chars = filter.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            ',' => {
+                patterns.push((std::mem::take(&mut current), wildcard));
+                wildcard = false;
+            }
+            '*' => wildcard = true,
+            _ => current.push(c),
+        }
+    }
+    patterns.push((current, wildcard));
+    patterns
+}
+
+fn query_value<'a>(query: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    query
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+fn split_url(url: &str) -> (String, Vec<(String, String)>) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+
+    let params = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect();
+
+    (path.to_string(), params)
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] == b'%'
+            && let Some(byte) = bytes
+                .get(index + 1..index + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            index += 3;
+            continue;
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[derive(Debug)]
+enum ServeError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, serde_yaml::Error),
+    Bind(u16, String),
+    Snapshot,
+}
+
+impl fmt::Display for ServeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServeError::Io(path, err) => write!(f, "Failed to read {}: {}", path.display(), err),
+            ServeError::Parse(path, err) => {
+                write!(f, "Failed to parse {} as YAML: {}", path.display(), err)
+            }
+            ServeError::Bind(port, err) => {
+                write!(f, "Failed to listen on 127.0.0.1:{port}: {err}")
+            }
+            ServeError::Snapshot => write!(f, "No configuration snapshot available to serve."),
+        }
+    }
+}